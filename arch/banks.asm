@@ -3,13 +3,32 @@
 #bankdef code
 {
     #addr 0x0000
-    #size 0x0100
-    #outp 0
+    #size 0x4000
+    #outp 8 * 0x0000
+}
+
+#bankdef rodata
+{
+    #addr 0x4000
+    #size 0x1000
+    #outp 8 * 0x4000
 }
 
 #bankdef data
 {
-    #addr 0x0100
-    #size 0x0010
-    #outp 8 * 0x0100
+    #addr 0x5000
+    #size 0x3000
+    #outp 8 * 0x5000
+}
+
+#bankdef bss
+{
+    #addr 0x8000
+    #size 0x6000
+}
+
+#bankdef stack
+{
+    #addr 0xe000
+    #size 0x2000
 }
\ No newline at end of file
@@ -0,0 +1,53 @@
+; RISC16 calling convention
+;
+; Routines are called with CALL, which stores the return address in r6 and
+; jumps to the routine. They return with RET, which jumps to the address in r6.
+;
+; Registers:
+; - r0: always zero
+; - r1, r2, r3: arguments and results, clobbered by calls
+; - r4: preserved across calls
+; - r5: assembler temporary, clobbered by calls, far jumps, branches and
+;   calls, and 16-bit ADDI/SUBI
+; - r6: return address, clobbered by calls
+; - r7: stack pointer, preserved across calls
+;
+; Arguments are passed in r1, r2 and r3, in that order. Results are returned
+; in r1, and a second result (such as a remainder) in r2. The flags are
+; clobbered by calls.
+;
+; The stack grows downwards and r7 points to the last pushed word. PUSH and
+; POP move one word. The emulator starts r7 at the top of the stack bank.
+;
+; JMP, Bcc and CALL to a target out of range of their offset load the target
+; into r5 and jump through it, and ADDI and SUBI with a 16-bit immediate load
+; it into r5, so r5 only holds values between such instructions. r6 is left
+; alone, and a far CALL still returns to the instruction after it.
+;
+; Stack frames are built with PROLOGUE and EPILOGUE. PROLOGUE pushes r6 and
+; r4, then reserves an optional (even) number of bytes for local
+; variables. EPILOGUE releases them, restores the registers and returns:
+;
+;     routine:
+;         PROLOGUE 4          ; two local words
+;         STW r1, [r7]        ; first local at r7 + 0
+;         ...
+;         CALL other
+;         ...
+;         EPILOGUE 4
+;
+; Frame layout, from r7 after PROLOGUE n:
+;
+;     r7 + 0 ... r7 + n - 1   local variables
+;     r7 + n                  saved r4
+;     r7 + n + 2              saved r6 (return address)
+;     r7 + n + 4              caller's frame
+;
+; Leaf routines that do not use r4 can skip the frame and return with RET
+; directly. Running the emulator with --check-abi reports calls that do not
+; preserve r4 and r7.
+
+; The routines in <std>/lib/ are placed in the code bank. Include them after
+; the program so that they do not come before its entry point.
+
+#once
\ No newline at end of file
@@ -0,0 +1,61 @@
+; Division. See <std>/lib/convention.asm for the calling convention.
+
+#once
+
+#bank code
+
+; udiv16: r1 = r1 / r2, r2 = r1 % r2 (unsigned)
+; Dividing by zero gives a quotient of 0xffff and returns the dividend as the
+; remainder. Clobbers r3.
+udiv16:
+    PUSH r4
+    MOV r3, 0
+    MOV r4, 16
+.loop:
+    SHL r1, r1, 1
+    ADC r3, r3, r3
+    BC .subtract
+    CMP r3, r2
+    BC .next
+.subtract:
+    SUB r3, r3, r2
+    INC r1
+.next:
+    DEC r4
+    BNZ .loop
+    MOV r2, r3
+    POP r4
+    RET
+
+; sdiv16: r1 = r1 / r2, r2 = r1 % r2 (signed)
+; The quotient is rounded towards zero and the remainder has the sign of the
+; dividend. Dividing by zero gives a quotient of -1, or 1 for a negative
+; dividend, and returns the dividend as the remainder. -32768 / -1 overflows
+; to -32768. Clobbers r3.
+sdiv16:
+    PUSH r6
+    PUSH r4
+    MOV r4, 0
+    TST r1
+    BNS .divisor
+    SUB r1, r0, r1
+    MOV r4, 0b11
+.divisor:
+    TST r2
+    BNS .divide
+    SUB r2, r0, r2
+    MOV r3, 0b01
+    XOR r4, r4, r3
+.divide:
+    CALL udiv16
+    SHR r3, r4, 1
+    BNC .remainder
+    SUB r1, r0, r1
+.remainder:
+    SHR r3, r4, 2
+    BNC .done
+    SUB r2, r0, r2
+.done:
+    POP r4
+    POP r6
+    RET
\ No newline at end of file
@@ -0,0 +1,94 @@
+; expect mem[results..results+4] == 0x8e,0x00,0x06,0x00
+; expect mem[results+4..results+8] == 0xff,0xff,0xd2,0x04
+; expect mem[results+8..results+12] == 0xff,0xff,0x00,0x00
+; expect mem[results+12..results+16] == 0x50,0xff,0xfe,0xff
+; expect mem[results+16..results+20] == 0x50,0xff,0x02,0x00
+; expect mem[results+20..results+24] == 0x03,0x00,0xff,0xff
+; expect mem[results+24..results+28] == 0x00,0x80,0x00,0x00
+; expect mem[results+28..results+32] == 0xff,0xff,0x05,0x00
+; expect mem[results+32..results+36] == 0x01,0x00,0xfb,0xff
+
+#bank code
+
+_start:
+    LA r4, results
+
+    LI r1, 1000
+    LI r2, 7
+    CALL udiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, 1234
+    LI r2, 0
+    CALL udiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, 0xffff
+    LI r2, 1
+    CALL udiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, -1234
+    LI r2, 7
+    CALL sdiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, 1234
+    LI r2, -7
+    CALL sdiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, -7
+    LI r2, -2
+    CALL sdiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, -32768
+    LI r2, -1
+    CALL sdiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, 5
+    LI r2, 0
+    CALL sdiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, -5
+    LI r2, 0
+    CALL sdiv16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+    HLT
+
+#include "<std>/lib/div.asm"
+
+#bank data
+
+results:
+    #res 36
\ No newline at end of file
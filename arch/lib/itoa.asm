@@ -0,0 +1,53 @@
+; Integer to decimal conversion. See <std>/lib/convention.asm for the calling
+; convention.
+
+#once
+
+#include "<std>/lib/div.asm"
+
+#bank code
+
+; utoa: writes the unsigned value of r1 in decimal at r2, followed by a null
+; byte. r1 = number of characters written, without the null byte.
+; The buffer must hold at least 6 bytes. Clobbers r2, r3, r5.
+utoa:
+    PROLOGUE
+    PUSH r2
+    MOV r4, 0
+.divide:
+    MOV r2, 10
+    CALL udiv16
+    ADDI r2, 0x30
+    PUSH r2
+    INC r4
+    TST r1
+    BNZ .divide
+    MOV r1, r4
+    SHL r3, r4, 1
+    ADD r3, r3, r7
+    LDW r5, [r3]
+.store:
+    POP r2
+    STL r2, [r5]
+    INC r5
+    DEC r4
+    BNZ .store
+    STL r0, [r5]
+    POP r2
+    EPILOGUE
+
+; itoa: writes the signed value of r1 in decimal at r2, followed by a null
+; byte. r1 = number of characters written, without the null byte.
+; The buffer must hold at least 7 bytes. Clobbers r2, r3, r5.
+itoa:
+    TST r1
+    BNS utoa
+    MOV r3, 0x2d
+    STL r3, [r2]
+    INC r2
+    SUB r1, r0, r1
+    PUSH r6
+    CALL utoa
+    POP r6
+    INC r1
+    RET
\ No newline at end of file
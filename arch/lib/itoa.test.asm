@@ -0,0 +1,50 @@
+; expect mem[zero..zero+2] == 0x30,0
+; expect mem[largest..largest+6] == 0x36,0x35,0x35,0x33,0x35,0
+; expect mem[smallest..smallest+7] == 0x2d,0x33,0x32,0x37,0x36,0x38,0
+; expect mem[negative..negative+3] == 0x2d,0x37,0
+; expect mem[lengths..lengths+8] == 1,0,5,0,6,0,2,0
+
+#bank code
+
+_start:
+    LA r4, lengths
+
+    LI r1, 0
+    LA r2, zero
+    CALL utoa
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LI r1, 0xffff
+    LA r2, largest
+    CALL utoa
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LI r1, -32768
+    LA r2, smallest
+    CALL itoa
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LI r1, -7
+    LA r2, negative
+    CALL itoa
+    STW r1, [r4]
+    ADDI r4, 2
+    HLT
+
+#include "<std>/lib/itoa.asm"
+
+#bank data
+
+zero:
+    #d8 0xff, 0xff
+largest:
+    #d8 0xff, 0xff, 0xff, 0xff, 0xff, 0xff
+smallest:
+    #d8 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff
+negative:
+    #d8 0xff, 0xff, 0xff
+lengths:
+    #res 8
@@ -0,0 +1,35 @@
+; Memory blocks. See <std>/lib/convention.asm for the calling convention.
+
+#once
+
+#bank code
+
+; memcpy: copies r3 bytes from r2 to r1
+; Clobbers r1, r2, r3.
+memcpy:
+    TST r3
+    BZ .done
+    PUSH r4
+.loop:
+    LDL r4, [r2]
+    STL r4, [r1]
+    INC r1
+    INC r2
+    DEC r3
+    BNZ .loop
+    POP r4
+.done:
+    RET
+
+; memset: sets r3 bytes at r1 to the low byte of r2
+; Clobbers r1, r3.
+memset:
+    TST r3
+    BZ .done
+.loop:
+    STL r2, [r1]
+    INC r1
+    DEC r3
+    BNZ .loop
+.done:
+    RET
\ No newline at end of file
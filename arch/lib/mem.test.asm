@@ -0,0 +1,45 @@
+; expect mem[destination..destination+6] == 2,3,5,7,11,0xaa
+; expect mem[filled..filled+5] == 0x2a,0x2a,0x2a,0x2a,0xbb
+; expect mem[untouched..untouched+2] == 0xcc,0xcc
+
+#bank code
+
+_start:
+    LA r1, destination
+    LA r2, source
+    MOV r3, 5
+    CALL memcpy
+
+    LA r1, filled
+    MOV r2, 0x122a
+    MOV r3, 4
+    CALL memset
+
+    LA r1, untouched
+    LA r2, source
+    MOV r3, 0
+    CALL memcpy
+
+    LA r1, untouched
+    MOV r2, 0
+    MOV r3, 0
+    CALL memset
+    HLT
+
+#include "<std>/lib/mem.asm"
+
+#bank rodata
+
+source:
+    #d8 2, 3, 5, 7, 11, 13
+
+#bank data
+
+destination:
+    #d8 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa
+
+filled:
+    #d8 0xbb, 0xbb, 0xbb, 0xbb, 0xbb
+
+untouched:
+    #d8 0xcc, 0xcc
@@ -0,0 +1,29 @@
+; Multiplication. See <std>/lib/convention.asm for the calling convention.
+
+#once
+
+#bank code
+
+; mul16: r1 = low word, r2 = high word of the unsigned product r1 * r2
+; The low word is also the low word of the signed product. Clobbers r3, r5.
+mul16:
+    PUSH r4
+    MOV r3, 0
+    MOV r4, 0
+    MOV r5, 0
+.loop:
+    TST r2
+    BZ .done
+    SHR r2, r2, 1
+    BNC .skip
+    ADD r3, r3, r1
+    ADC r5, r5, r4
+.skip:
+    SHL r1, r1, 1
+    ADC r4, r4, r4
+    JMP .loop
+.done:
+    MOV r1, r3
+    MOV r2, r5
+    POP r4
+    RET
\ No newline at end of file
@@ -0,0 +1,49 @@
+; expect mem[results..results+4] == 0xf0,0x0d,0x01,0x00
+; expect mem[results+4..results+8] == 0x01,0x00,0xfe,0xff
+; expect mem[results+8..results+12] == 0,0,0,0
+; expect mem[results+12..results+16] == 0xeb,0xff,0x06,0x00
+
+#bank code
+
+_start:
+    LA r4, results
+
+    LI r1, 1234
+    LI r2, 56
+    CALL mul16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, 0xffff
+    LI r2, 0xffff
+    CALL mul16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, 0x1234
+    LI r2, 0
+    CALL mul16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+
+    LI r1, -3
+    LI r2, 7
+    CALL mul16
+    STW r1, [r4]
+    ADDI r4, 2
+    STW r2, [r4]
+    ADDI r4, 2
+    HLT
+
+#include "<std>/lib/mul.asm"
+
+#bank data
+
+results:
+    #res 16
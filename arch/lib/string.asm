@@ -0,0 +1,44 @@
+; Null-terminated strings. See <std>/lib/convention.asm for the calling
+; convention.
+
+#once
+
+#bank code
+
+; string_length: r1 = length of the string at r1
+; Clobbers r2, r3.
+string_length:
+    MOV r2, r1
+    MOV r1, 0
+.loop:
+    LDL r3, [r2]
+    CMPL r3, r0
+    BZ .done
+    INC r1
+    INC r2
+    JMP .loop
+.done:
+    RET
+
+; string_compare: compares the strings at r1 and r2
+; r1 is zero if they are equal, otherwise the difference between the first
+; differing bytes, which is negative if the string at r1 sorts first.
+; Clobbers r2, r3.
+string_compare:
+    PUSH r4
+.loop:
+    MOV r3, 0
+    MOV r4, 0
+    LDL r3, [r1]
+    LDL r4, [r2]
+    CMP r3, r4
+    BNZ .done
+    TST r3
+    BZ .done
+    INC r1
+    INC r2
+    JMP .loop
+.done:
+    SUB r1, r3, r4
+    POP r4
+    RET
\ No newline at end of file
@@ -0,0 +1,58 @@
+; expect mem[results..results+4] == 5,0,0,0
+; expect mem[results+4..results+10] == 0,0,0xff,0xff,0x64,0x00
+
+#bank code
+
+_start:
+    LA r4, results
+
+    LA r1, hello
+    CALL string_length
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LA r1, empty
+    CALL string_length
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LA r1, hello
+    LA r2, hello_copy
+    CALL string_compare
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LA r1, abc
+    LA r2, abd
+    CALL string_compare
+    STW r1, [r4]
+    ADDI r4, 2
+
+    LA r1, abd
+    LA r2, ab
+    CALL string_compare
+    STW r1, [r4]
+    ADDI r4, 2
+    HLT
+
+#include "<std>/lib/string.asm"
+
+#bank rodata
+
+hello:
+    #d "hello\0"
+hello_copy:
+    #d "hello\0"
+empty:
+    #d8 0
+abc:
+    #d "abc\0"
+abd:
+    #d "abd\0"
+ab:
+    #d "ab\0"
+
+#bank data
+
+results:
+    #res 10
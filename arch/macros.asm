@@ -17,6 +17,260 @@
         ADD {rd}, {rs}, r0
     }
 
+    LI {rd: register}, {imm: i16} => {
+        assert(imm & 0xffff == 0)
+        asm {
+            XOR {rd}, {rd}, {rd}
+        }
+    }
+
+    LI {rd: register}, {imm: i16} => {
+        assert(imm & 0xffff == 0xffff)
+        asm {
+            NOT {rd}, r0
+        }
+    }
+
+    LI {rd: register}, {imm: i16} => {
+        value = imm & 0xffff
+        asm {
+            MOV {rd}, {value}
+        }
+    }
+
+    LA {rd: register}, {addr: u16} => asm {
+        MOV {rd}, {addr}
+    }
+
+    CLR {rd: register} => asm {
+        XOR {rd}, {rd}, {rd}
+    }
+
+    NEG {rd: register} => asm {
+        SUB {rd}, r0, {rd}
+    }
+
+    NEG {rd: register}, {rs: register} => asm {
+        SUB {rd}, r0, {rs}
+    }
+
+    ADDI {rd: register}, {imm: i16} => {
+        value = 0x10000 - (imm & 0xffff)
+        assert(imm < 0 || imm > 0xff)
+        assert(value <= 0xff)
+        asm {
+            SUBI {rd}, {value}
+        }
+    }
+
+    ADDI {rd: register}, {imm: i16} => {
+        assert(imm & 0xffff > 0xff && imm & 0xffff < 0xff01)
+        assert(rd != 0b101)
+        value = imm & 0xffff
+        asm {
+            MOV r5, {value}
+            ADD {rd}, {rd}, r5
+        }
+    }
+
+    SUBI {rd: register}, {imm: i16} => {
+        value = (0x10000 - imm) & 0xffff
+        assert(imm < 0 || imm > 0xff)
+        assert(value <= 0xff)
+        asm {
+            ADDI {rd}, {value}
+        }
+    }
+
+    SUBI {rd: register}, {imm: i16} => {
+        assert(imm & 0xffff > 0xff && imm & 0xffff < 0xff01)
+        assert(rd != 0b101)
+        value = imm & 0xffff
+        asm {
+            MOV r5, {value}
+            SUB {rd}, {rd}, r5
+        }
+    }
+
+    JMP {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0x7ff || relative_address < !0x7ff)
+        asm {
+            MOV r5, {addr}
+            JMP [r5]
+        }
+    }
+
+    BC {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BNC {skip}
+            JMP {addr}
+        }
+    }
+
+    BNC {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BC {skip}
+            JMP {addr}
+        }
+    }
+
+    BO {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BNO {skip}
+            JMP {addr}
+        }
+    }
+
+    BNO {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BO {skip}
+            JMP {addr}
+        }
+    }
+
+    BZ {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BNZ {skip}
+            JMP {addr}
+        }
+    }
+
+    BNZ {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BZ {skip}
+            JMP {addr}
+        }
+    }
+
+    BS {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BNS {skip}
+            JMP {addr}
+        }
+    }
+
+    BNS {addr: u16} => {
+        relative_address = (addr - pc - 2) >> 1
+        assert(relative_address > 0xff || relative_address < !0xff)
+        jump = (addr - pc - 4) >> 1
+        skip = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 4 : 8)
+        asm {
+            BS {skip}
+            JMP {addr}
+        }
+    }
+
+    BEQ {ra: register}, {rb: register}, {addr: u16} => asm {
+        CMP {ra}, {rb}
+        BZ {addr}
+    }
+
+    BNE {ra: register}, {rb: register}, {addr: u16} => asm {
+        CMP {ra}, {rb}
+        BNZ {addr}
+    }
+
+    BLTU {ra: register}, {rb: register}, {addr: u16} => asm {
+        CMP {ra}, {rb}
+        BC {addr}
+    }
+
+    BGEU {ra: register}, {rb: register}, {addr: u16} => asm {
+        CMP {ra}, {rb}
+        BNC {addr}
+    }
+
+    BLT {ra: register}, {rb: register}, {addr: u16} => {
+        first = (addr - pc - 6) >> 1
+        second = (addr - pc - 10) >> 1
+        assert(first <= 0xff && first >= !0xff)
+        assert(second <= 0xff && second >= !0xff)
+        overflow = pc + 8
+        done = pc + 10
+        asm {
+            CMP {ra}, {rb}
+            BO {overflow}
+            BS {addr}
+            BNS {done}
+            BNS {addr}
+        }
+    }
+
+    BLT {ra: register}, {rb: register}, {addr: u16} => {
+        overflow = pc + 8
+        taken = pc + 10
+        jump = (addr - pc - 12) >> 1
+        done = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 12 : 16)
+        asm {
+            CMP {ra}, {rb}
+            BO {overflow}
+            BNS {done}
+            BS {taken}
+            BS {done}
+            JMP {addr}
+        }
+    }
+
+    BGE {ra: register}, {rb: register}, {addr: u16} => {
+        first = (addr - pc - 6) >> 1
+        second = (addr - pc - 10) >> 1
+        assert(first <= 0xff && first >= !0xff)
+        assert(second <= 0xff && second >= !0xff)
+        overflow = pc + 8
+        done = pc + 10
+        asm {
+            CMP {ra}, {rb}
+            BO {overflow}
+            BNS {addr}
+            BS {done}
+            BS {addr}
+        }
+    }
+
+    BGE {ra: register}, {rb: register}, {addr: u16} => {
+        overflow = pc + 8
+        taken = pc + 10
+        jump = (addr - pc - 12) >> 1
+        done = pc + ((jump <= 0x7ff && jump >= !0x7ff) ? 12 : 16)
+        asm {
+            CMP {ra}, {rb}
+            BO {overflow}
+            BS {done}
+            BNS {taken}
+            BNS {done}
+            JMP {addr}
+        }
+    }
+
     TST {rs: register} => asm {
         CMP {rs}, r0
     }
@@ -28,4 +282,66 @@
     DEC {rd: register} => asm {
         SUBI {rd}, 1
     }
+
+    PUSH {rs: register} => asm {
+        SUBI r7, 2
+        STW {rs}, [r7]
+    }
+
+    POP {rd: register} => asm {
+        LDW {rd}, [r7]
+        ADDI r7, 2
+    }
+
+    CALL {addr: u16} => {
+        relative_address = (addr - pc - 6) >> 1
+        assert(relative_address <= 0x7ff && relative_address >= !0x7ff)
+        return_address = pc + 6
+        asm {
+            MOV r6, {return_address}
+            JMP {addr}
+        }
+    }
+
+    CALL {addr: u16} => {
+        relative_address = (addr - pc - 6) >> 1
+        assert(relative_address > 0x7ff || relative_address < !0x7ff)
+        return_address = pc + 10
+        asm {
+            MOV r6, {return_address}
+            MOV r5, {addr}
+            JMP [r5]
+        }
+    }
+
+    RET => asm {
+        JMP [r6]
+    }
+
+    PROLOGUE => asm {
+        PUSH r6
+        PUSH r4
+    }
+
+    PROLOGUE {size: u8} => {
+        assert(size % 2 == 0)
+        asm {
+            PROLOGUE
+            SUBI r7, {size}
+        }
+    }
+
+    EPILOGUE => asm {
+        POP r4
+        POP r6
+        RET
+    }
+
+    EPILOGUE {size: u8} => {
+        assert(size % 2 == 0)
+        asm {
+            ADDI r7, {size}
+            EPILOGUE
+        }
+    }
 }
\ No newline at end of file
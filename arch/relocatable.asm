@@ -0,0 +1,17 @@
+#once
+
+#subruledef off9 {
+    {addr: u16} => {
+		relative_address = (addr - pc - 2) >> 1
+		relative_address`9
+	}
+    
+}
+
+#subruledef off12 {
+    {addr: u16} => {
+		relative_address = (addr - pc - 2) >> 1
+		relative_address`12
+	}
+    
+}
@@ -11,10 +11,9 @@
 #bank code
 
     MOVL r2, 1      ; r2 = F(1)
-    MOVH r3, 0x01   ; r3 = 0x0100 (initial memory address)
+    MOV r3, sequence ; r3 = initial memory address
     MOVL r4, 0xff   ; r4 = 0x00ff (maximum value)
 
-    INC r3          ; increment memory pointer
     STL r2, [r3]    ; store F(1) in memory
     
 loop:
@@ -28,4 +27,9 @@ loop:
     JMP loop        ; loop again
 
 end:
-    HLT             ; halt the program
\ No newline at end of file
+    HLT             ; halt the program
+
+#bank data
+
+sequence:
+    #res 16
\ No newline at end of file
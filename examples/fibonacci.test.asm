@@ -0,0 +1,5 @@
+; expect r2 == 0x00e9
+; expect mem[sequence..sequence+13] == 1,1,2,3,5,8,13,21,34,55,89,144,233
+; expect mem[sequence+13] == 0
+
+#include "fibonacci.asm"
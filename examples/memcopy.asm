@@ -1,7 +1,7 @@
 #bank code
 
-    MOVH r1, 0x01
-    MOVH r2, 0x02
+    MOV r1, source
+    MOV r2, destination
     MOVL r3, 0x10
 
 loop:
@@ -14,6 +14,12 @@ loop:
 
     HLT
 
+#bank rodata
+
+source:
+    #d8 2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53
+
 #bank data
 
-#d8 2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53
\ No newline at end of file
+destination:
+    #res 16
\ No newline at end of file
@@ -0,0 +1,4 @@
+; expect r3 == 0
+; expect mem[destination..destination+16] == 2,3,5,7,11,13,17,19,23,29,31,37,41,43,47,53
+
+#include "memcopy.asm"
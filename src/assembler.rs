@@ -1,6 +1,16 @@
-use anyhow::{Result, bail};
+use crate::emulator::Cpu;
+use crate::executable::{Executable, IsaProfile, LineEntry, Symbol};
+use crate::formats::{OutputFormat, Segment, write_image};
+use crate::instructions;
+use crate::layout::Layout;
+use crate::object::{Export, Object, Relocation, RelocationKind, RelocationTarget, Section};
+use anyhow::{Context, Result, bail};
 use customasm::{asm, diagn, util};
+use std::collections::HashMap;
+use std::fmt::Write;
 use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 
 const STD_FILES: &[(&str, &str)] = &[
     (
@@ -9,21 +19,219 @@ const STD_FILES: &[(&str, &str)] = &[
     ),
     ("<std>/banks.asm", include_str!("../arch/banks.asm")),
     (
-        "<std>/instructions.asm",
-        include_str!("../arch/instructions.asm"),
+        "<std>/lib/convention.asm",
+        include_str!("../arch/lib/convention.asm"),
+    ),
+    ("<std>/lib/div.asm", include_str!("../arch/lib/div.asm")),
+    ("<std>/lib/itoa.asm", include_str!("../arch/lib/itoa.asm")),
+    ("<std>/lib/mem.asm", include_str!("../arch/lib/mem.asm")),
+    ("<std>/lib/mul.asm", include_str!("../arch/lib/mul.asm")),
+    (
+        "<std>/lib/string.asm",
+        include_str!("../arch/lib/string.asm"),
     ),
     ("<std>/macros.asm", include_str!("../arch/macros.asm")),
+    ("<std>/offsets.asm", include_str!("../arch/offsets.asm")),
     ("<std>/types.asm", include_str!("../arch/types.asm")),
 ];
 
-pub fn assemble_to_binary(input: &str) -> Result<Vec<u8>> {
-    let program = fs::read_to_string(input)?;
+const LISTING_BYTES_PER_ROW: usize = 4;
+const ENTRY_SYMBOL: &str = "_start";
+
+const RELOCATABLE_OFFSETS: &str = include_str!("../arch/relocatable.asm");
+
+const PRELUDE: &str = "<prelude>";
+const OBJECT_SECTIONS: &[&str] = &["code", "rodata", "data"];
+const OBJECT_SECTION_SIZE: usize = 0x8000;
+const PROBE_DELTA: u16 = 0x0102;
+const BRANCHES: &[&str] = &[
+    "JMP", "CALL", "BC", "BNC", "BO", "BNO", "BZ", "BNZ", "BS", "BNS", "BEQ", "BNE", "BLTU",
+    "BGEU", "BLT", "BGE",
+];
+const ASSEMBLER_TEMPORARY: usize = 5;
+
+struct Assembly {
+    output: util::BitVec,
+    decls: asm::ItemDecls,
+    defs: asm::ItemDefs,
+    fileserver: util::FileServerMock,
+}
+
+#[derive(Clone, Default)]
+pub struct AssemblerOptions {
+    pub layout: Layout,
+    pub include_dirs: Vec<String>,
+    pub defines: Vec<Define>,
+    pub report_relaxations: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Define {
+    pub name: String,
+    pub value: i64,
+}
+
+impl FromStr for Define {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, value) = s.split_once('=').unwrap_or((s, "1"));
+        if name.is_empty()
+            || name.starts_with(|c: char| c.is_ascii_digit())
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            anyhow::bail!("invalid define name {name}");
+        }
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value),
+        };
+        let magnitude = if let Some(hex) = digits.strip_prefix("0x") {
+            i64::from_str_radix(hex, 16)
+        } else if let Some(bin) = digits.strip_prefix("0b") {
+            i64::from_str_radix(bin, 2)
+        } else {
+            digits.parse::<i64>()
+        }
+        .with_context(|| format!("invalid value for define {name}"))?;
+        Ok(Define {
+            name: name.to_string(),
+            value: if negative { -magnitude } else { magnitude },
+        })
+    }
+}
+
+#[derive(Default)]
+struct Sources {
+    files: Vec<(String, String)>,
+    imports: Vec<String>,
+}
+
+#[derive(Default)]
+struct Overrides {
+    files: Vec<(&'static str, String)>,
+    constants: Vec<(String, i64)>,
+    quiet: bool,
+}
+
+fn preprocess(program: &str) -> (String, Vec<String>) {
+    let mut imports = Vec::new();
+    let mut result = String::with_capacity(program.len());
+
+    for line in program.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        match trimmed.strip_prefix("#import") {
+            Some(names) if names.starts_with(char::is_whitespace) => {
+                let names = names.split(';').next().unwrap_or("");
+                imports.extend(
+                    names
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string),
+                );
+                result.push_str(&line[..line.len() - trimmed.len()]);
+                result.push(';');
+                result.push_str(&trimmed[1..]);
+            }
+            _ => result.push_str(line),
+        }
+    }
+
+    (result, imports)
+}
+
+fn includes(program: &str) -> Vec<&str> {
+    program
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("#include"))
+        .filter_map(|rest| rest.trim_start().strip_prefix('"'))
+        .filter_map(|rest| rest.split_once('"').map(|(filename, _)| filename))
+        .collect()
+}
+
+fn read_sources(input: &str, include_dirs: &[String]) -> Result<Sources> {
+    let mut sources = Sources::default();
+    read_source(&mut sources, input, Path::new(input), include_dirs)?;
+    Ok(sources)
+}
+
+fn read_source(
+    sources: &mut Sources,
+    name: &str,
+    path: &Path,
+    include_dirs: &[String],
+) -> Result<()> {
+    if sources.files.iter().any(|(existing, _)| existing == name) {
+        return Ok(());
+    }
+
+    let program =
+        fs::read_to_string(path).with_context(|| format!("unable to read {}", path.display()))?;
+    let (program, imports) = preprocess(&program);
+    sources.imports.extend(imports);
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let mut included = Vec::new();
+    for filename in includes(&program) {
+        if util::is_std_path(filename) {
+            continue;
+        }
+        let Ok(included_name) = util::filename_navigate(
+            &mut diagn::Report::new(),
+            diagn::Span::new_dummy(),
+            name,
+            filename,
+        ) else {
+            continue;
+        };
+        if let Some(found) = std::iter::once(directory.join(filename))
+            .chain(include_dirs.iter().map(|dir| Path::new(dir).join(filename)))
+            .find(|candidate| candidate.is_file())
+        {
+            included.push((included_name, found));
+        }
+    }
+
+    sources.files.push((name.to_string(), program));
+    for (included_name, found) in included {
+        read_source(sources, &included_name, &found, include_dirs)?;
+    }
+    Ok(())
+}
+
+fn assemble(input: &str, options: &AssemblerOptions) -> Result<Assembly> {
+    assemble_with(input, options, &Overrides::default())
+}
+
+fn assemble_with(
+    input: &str,
+    options: &AssemblerOptions,
+    overrides: &Overrides,
+) -> Result<Assembly> {
+    let sources = read_sources(input, &options.include_dirs)?;
 
     let mut report = diagn::Report::new();
     let mut fileserver = util::FileServerMock::new();
 
     fileserver.add_std_files(STD_FILES);
-    fileserver.add(input, program);
+    fileserver.add("<std>/banks.asm", options.layout.bankdefs());
+    fileserver.add("<std>/instructions.asm", instructions::ruledef());
+    for (filename, contents) in &overrides.files {
+        fileserver.add(*filename, contents.as_str());
+    }
+
+    let mut prelude = String::new();
+    let defines = options
+        .defines
+        .iter()
+        .map(|define| (define.name.clone(), define.value));
+    for (name, value) in defines.chain(overrides.constants.iter().cloned()) {
+        writeln!(prelude, "{name} = {value}")?;
+    }
+    fileserver.add(PRELUDE, prelude);
+    for (filename, program) in sources.files {
+        fileserver.add(filename, program);
+    }
 
     let opts = asm::AssemblyOptions::new();
 
@@ -31,22 +239,610 @@ pub fn assemble_to_binary(input: &str) -> Result<Vec<u8>> {
         &mut report,
         &opts,
         &mut fileserver,
-        &["<std>/architecture.asm", input],
+        &["<std>/architecture.asm", PRELUDE, input],
     );
 
-    let result = assembly.output.map(|output| output.format_binary());
-
-    report.print_all(&mut std::io::stderr().lock(), &fileserver, true);
+    if !overrides.quiet || assembly.output.is_none() {
+        report.print_all(&mut std::io::stderr().lock(), &fileserver, true);
+    }
 
-    if let Some(result) = result {
-        Ok(result)
-    } else {
+    let (Some(output), Some(decls), Some(defs)) = (assembly.output, assembly.decls, assembly.defs)
+    else {
         bail!("unable to assemble program");
+    };
+    let mut assembly = Assembly {
+        output,
+        decls,
+        defs,
+        fileserver,
+    };
+
+    if options.report_relaxations && !overrides.quiet {
+        let relaxations = format_relaxations(&mut assembly);
+        for relaxation in &relaxations {
+            eprintln!("{relaxation}");
+        }
+        eprintln!("{} branches relaxed", relaxations.len());
+    }
+
+    Ok(assembly)
+}
+
+fn format_relaxations(assembly: &mut Assembly) -> Vec<String> {
+    use instructions::Instruction;
+    use util::FileServer;
+
+    let binary = assembly.output.format_binary();
+    let mut relaxations = Vec::new();
+    for span in &assembly.output.spans {
+        let (Some(offset), Some((start, _))) = (span.offset, span.span.location()) else {
+            continue;
+        };
+        let filename = assembly.fileserver.get_filename(span.span.file_handle);
+        if filename.starts_with("<std>") {
+            continue;
+        }
+        let filename = filename.to_string();
+        let source = assembly.fileserver.get_str_unwrap(span.span.file_handle);
+        let mnemonic = source[start..]
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_uppercase();
+        if !BRANCHES.contains(&mnemonic.as_str()) {
+            continue;
+        }
+
+        let size = span.size / 8;
+        let instructions = binary[offset / 8..offset / 8 + size]
+            .chunks_exact(2)
+            .filter_map(|word| Instruction::decode(u16::from_le_bytes([word[0], word[1]])))
+            .collect::<Vec<_>>();
+        let far = instructions
+            .iter()
+            .filter(|instruction| {
+                matches!(instruction, Instruction::JumpToPointer { rs } if *rs == ASSEMBLER_TEMPORARY)
+            })
+            .count();
+        let far = if size > 2 { far } else { 0 };
+        let skip = !matches!(mnemonic.as_str(), "JMP" | "CALL")
+            && instructions.iter().any(|instruction| {
+                matches!(
+                    instruction,
+                    Instruction::JumpToOffset { .. } | Instruction::JumpToPointer { .. }
+                )
+            });
+        if far == 0 && !skip {
+            continue;
+        }
+
+        let near = size - 4 * far - if skip { 2 } else { 0 };
+        let line = source[..start].matches('\n').count() + 1;
+        let address = span.addr.maybe_into::<u16>().unwrap_or(0);
+        relaxations.push(format!(
+            "{filename}:{line}: {mnemonic} at 0x{address:04x} relaxed from {near} to {size} bytes"
+        ));
+    }
+    relaxations
+}
+
+pub fn assemble_to_binary(input: &str, options: &AssemblerOptions) -> Result<Vec<u8>> {
+    let assembly = assemble(input, options)?;
+    Ok(assembly.output.format_binary())
+}
+
+pub fn assemble_to_executable(input: &str, options: &AssemblerOptions) -> Result<Executable> {
+    let mut assembly = assemble(input, options)?;
+    Ok(format_executable(&mut assembly))
+}
+
+pub fn assemble_to_object(input: &str, options: &AssemblerOptions) -> Result<Object> {
+    let imports = read_sources(input, &options.include_dirs)?.imports;
+
+    let probe = |bases: &[u16], values: &[u16], quiet: bool| -> Result<Probe> {
+        let mut banks = String::from("#once\n");
+        for (index, (name, base)) in OBJECT_SECTIONS.iter().zip(bases).enumerate() {
+            writeln!(banks, "\n#bankdef {name}\n{{")?;
+            writeln!(banks, "    #addr 0x{base:04x}")?;
+            writeln!(
+                banks,
+                "    #size 0x{:04x}",
+                OBJECT_SECTION_SIZE - *base as usize
+            )?;
+            writeln!(banks, "    #outp 8 * 0x{:04x}", index * OBJECT_SECTION_SIZE)?;
+            writeln!(banks, "}}")?;
+        }
+
+        let overrides = Overrides {
+            files: vec![
+                ("<std>/banks.asm", banks),
+                ("<std>/offsets.asm", RELOCATABLE_OFFSETS.to_string()),
+            ],
+            constants: imports
+                .iter()
+                .cloned()
+                .zip(values.iter().map(|value| *value as i64))
+                .collect(),
+            quiet,
+        };
+        let assembly = assemble_with(input, options, &overrides)?;
+        Ok(Probe::new(&assembly))
+    };
+
+    let zero_bases = vec![0; OBJECT_SECTIONS.len()];
+    let zero_values = vec![0; imports.len()];
+    let base = probe(&zero_bases, &zero_values, false)?;
+
+    let mut probes = Vec::new();
+    for index in 0..OBJECT_SECTIONS.len() {
+        let mut bases = zero_bases.clone();
+        bases[index] = PROBE_DELTA;
+        probes.push((
+            RelocationTarget::Section(index as u16),
+            probe(&bases, &zero_values, true)?,
+        ));
+    }
+    for index in 0..imports.len() {
+        let mut values = zero_values.clone();
+        values[index] = PROBE_DELTA;
+        probes.push((
+            RelocationTarget::Import(index as u16),
+            probe(&zero_bases, &values, true)?,
+        ));
+    }
+
+    let mut relocations = Vec::new();
+    for (section, data) in base.sections.iter().enumerate() {
+        for (_, probe) in &probes {
+            if probe.sections[section].len() != data.len() {
+                bail!(
+                    "size of section {} depends on symbol values",
+                    OBJECT_SECTIONS[section]
+                );
+            }
+        }
+
+        let mut offset = 0;
+        while offset + 1 < data.len() {
+            let word = u16::from_le_bytes([data[offset], data[offset + 1]]);
+
+            let mut targets = Vec::new();
+            for (target, probe) in &probes {
+                let other = &probe.sections[section];
+                if other[offset] != data[offset] {
+                    targets.push((
+                        *target,
+                        u16::from_le_bytes([other[offset], other[offset + 1]]),
+                    ));
+                }
+            }
+            if targets.is_empty() {
+                offset += 1;
+                continue;
+            }
+
+            let own = RelocationTarget::Section(section as u16);
+            let kind = match targets[0].1.wrapping_sub(word) {
+                PROBE_DELTA if targets.len() == 1 => RelocationKind::Word,
+                _ if offset % 2 == 1 => bail!(
+                    "unsupported reference at offset 0x{offset:04x} of section {}",
+                    OBJECT_SECTIONS[section]
+                ),
+                _ => match word >> 12 {
+                    0x5 if word & 1 == 0 => RelocationKind::Low,
+                    0x5 => RelocationKind::High,
+                    0x8 => RelocationKind::Off12,
+                    0xa => RelocationKind::Off9,
+                    _ => bail!(
+                        "unsupported reference at offset 0x{offset:04x} of section {}",
+                        OBJECT_SECTIONS[section]
+                    ),
+                },
+            };
+            if let RelocationKind::Off9 | RelocationKind::Off12 = kind {
+                targets.retain(|(target, _)| *target != own);
+            }
+            let [(target, _)] = targets[..] else {
+                bail!(
+                    "unsupported reference at offset 0x{offset:04x} of section {}",
+                    OBJECT_SECTIONS[section]
+                );
+            };
+            if let (RelocationKind::Off9 | RelocationKind::Off12, RelocationTarget::Section(_)) =
+                (kind, target)
+            {
+                bail!(
+                    "relative reference across sections at offset 0x{offset:04x} of section {}",
+                    OBJECT_SECTIONS[section]
+                );
+            }
+
+            let addend = match kind {
+                RelocationKind::Word => word as i32,
+                RelocationKind::Low | RelocationKind::High => ((word >> 1) & 0xff) as i32,
+                RelocationKind::Off9 => {
+                    let field = ((word >> 3) & 0x1ff) as i32;
+                    ((field * 2 + offset as i32 + 2) << 22) >> 22
+                }
+                RelocationKind::Off12 => {
+                    let field = (word & 0xfff) as i32;
+                    ((field * 2 + offset as i32 + 2) << 19) >> 19
+                }
+            };
+
+            relocations.push(Relocation {
+                section: section as u16,
+                offset: offset as u16,
+                kind,
+                target,
+                addend,
+            });
+            offset += 2;
+        }
+    }
+
+    for index in 0..relocations.len() {
+        let (head, tail) = relocations.split_at_mut(index + 1);
+        let high = &mut head[index];
+        if high.kind != RelocationKind::High {
+            continue;
+        }
+        let high_byte = high.addend;
+        high.addend = high_byte << 8;
+        if let Some(low) = tail.first_mut()
+            && low.kind == RelocationKind::Low
+            && low.section == high.section
+            && low.offset == high.offset + 2
+            && low.target == high.target
+        {
+            low.addend |= high_byte << 8;
+            high.addend = low.addend;
+        }
+    }
+
+    let mut exports = Vec::new();
+    for (name, value) in &base.symbols {
+        if name.contains('.') {
+            continue;
+        }
+        let section = probes
+            .iter()
+            .take(OBJECT_SECTIONS.len())
+            .position(|(_, probe)| {
+                probe.symbols.get(name) == Some(&value.wrapping_add(PROBE_DELTA))
+            });
+        if let Some(section) = section {
+            exports.push(Export {
+                name: name.clone(),
+                section: section as u16,
+                offset: *value,
+            });
+        }
+    }
+    exports.sort_by_key(|export| (export.section, export.offset));
+
+    let mut sections: Vec<Section> = OBJECT_SECTIONS
+        .iter()
+        .zip(base.sections)
+        .map(|(name, data)| Section {
+            name: name.to_string(),
+            data,
+        })
+        .collect();
+    sections.retain(|section| !section.data.is_empty());
+    let renumber = |index: u16| {
+        let name = OBJECT_SECTIONS[index as usize];
+        sections
+            .iter()
+            .position(|section| section.name == name)
+            .unwrap_or(0) as u16
+    };
+    for relocation in &mut relocations {
+        relocation.section = renumber(relocation.section);
+        if let RelocationTarget::Section(target) = relocation.target {
+            relocation.target = RelocationTarget::Section(renumber(target));
+        }
+    }
+    for export in &mut exports {
+        export.section = renumber(export.section);
     }
+
+    Ok(Object {
+        sections,
+        exports,
+        imports,
+        relocations,
+    })
+}
+
+struct Probe {
+    sections: Vec<Vec<u8>>,
+    symbols: HashMap<String, u16>,
+}
+
+impl Probe {
+    fn new(assembly: &Assembly) -> Probe {
+        let binary = assembly.output.format_binary();
+        let sections = (0..OBJECT_SECTIONS.len())
+            .map(|index| {
+                let start = index * OBJECT_SECTION_SIZE;
+                let end = assembly
+                    .output
+                    .spans
+                    .iter()
+                    .filter(|span| span.size > 0)
+                    .filter_map(|span| {
+                        span.offset
+                            .map(|offset| (offset / 8, (offset + span.size) / 8))
+                    })
+                    .filter(|(offset, _)| *offset >= start && *offset < start + OBJECT_SECTION_SIZE)
+                    .map(|(_, end)| end)
+                    .max()
+                    .unwrap_or(start);
+                binary.get(start..end).unwrap_or_default().to_vec()
+            })
+            .collect();
+        let symbols = format_symbols(assembly)
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.address))
+            .collect();
+        Probe { sections, symbols }
+    }
+}
+
+pub fn assemble_to_listing(input: &str, options: &AssemblerOptions) -> Result<String> {
+    let mut assembly = assemble(input, options)?;
+    format_listing(&mut assembly, input)
 }
 
-pub fn assemble_to_file(input: String, output: String) -> Result<()> {
-    let bytes = assemble_to_binary(input.as_str())?;
-    fs::write(output, bytes)?;
+fn format_segments(output: &util::BitVec) -> Vec<Segment> {
+    let binary = output.format_binary();
+    output
+        .get_blocks()
+        .iter()
+        .map(|block| Segment {
+            address: (block.offset / 8) as u16,
+            data: binary[block.offset / 8..(block.offset + block.size) / 8].to_vec(),
+        })
+        .collect()
+}
+
+fn format_symbols(assembly: &Assembly) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    assembly.decls.symbols.format(
+        &assembly.decls,
+        &assembly.defs,
+        &mut |_, decl, name, value| {
+            if let util::SymbolKind::Label = decl.kind
+                && let Some(address) = value.maybe_into::<u16>()
+            {
+                symbols.push(Symbol {
+                    name: name.to_string(),
+                    address,
+                });
+            }
+        },
+    );
+    symbols
+}
+
+fn format_executable(assembly: &mut Assembly) -> Executable {
+    use util::FileServer;
+
+    let mut files = Vec::new();
+    let mut sources = Vec::new();
+    let mut lines = Vec::new();
+
+    for span in &assembly.output.spans {
+        let (Some(_), Some((start, _))) = (span.offset, span.span.location()) else {
+            continue;
+        };
+        if span.size == 0 {
+            continue;
+        }
+        let filename = assembly.fileserver.get_filename(span.span.file_handle);
+        let file = match files.iter().position(|file| file == filename) {
+            Some(file) => file,
+            None => {
+                files.push(filename.to_string());
+                sources.push(assembly.fileserver.get_str_unwrap(span.span.file_handle));
+                files.len() - 1
+            }
+        };
+        let line = sources[file][..start].matches('\n').count() + 1;
+
+        lines.push(LineEntry {
+            address: span.addr.maybe_into::<u16>().unwrap_or(0),
+            size: (span.size / 8) as u16,
+            file: file as u16,
+            line: line as u32,
+        });
+    }
+    lines.sort_by_key(|entry| entry.address);
+
+    let symbols = format_symbols(assembly);
+    let entry = symbols
+        .iter()
+        .find(|symbol| symbol.name == ENTRY_SYMBOL)
+        .map_or(0, |symbol| symbol.address);
+
+    Executable {
+        profile: IsaProfile::Base,
+        entry,
+        segments: format_segments(&assembly.output),
+        symbols,
+        files,
+        lines,
+    }
+}
+
+fn format_listing(assembly: &mut Assembly, input: &str) -> Result<String> {
+    use util::FileServer;
+
+    let binary = assembly.output.format_binary();
+
+    let handle = assembly.fileserver.get_handle_unwrap(input);
+    let source = assembly.fileserver.get_str_unwrap(handle);
+
+    let mut line_starts = vec![0];
+    line_starts.extend(source.match_indices('\n').map(|(index, _)| index + 1));
+
+    let mut spans_by_line = vec![Vec::new(); line_starts.len()];
+    for span in &assembly.output.spans {
+        let (Some(offset), Some((start, _))) = (span.offset, span.span.location()) else {
+            continue;
+        };
+        if span.span.file_handle != handle {
+            continue;
+        }
+        let line = line_starts.partition_point(|&line_start| line_start <= start) - 1;
+        let address = span.addr.maybe_into::<u16>().unwrap_or(0);
+        spans_by_line[line].push((address, offset / 8, span.size / 8, start));
+    }
+
+    let mut listing = String::new();
+    writeln!(listing, " line  addr  {:<11}  source", "bytes")?;
+    writeln!(listing)?;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let spans = &mut spans_by_line[index];
+        spans.sort_by_key(|&(_, _, _, start)| start);
+
+        if spans.is_empty() {
+            writeln!(listing, "{line:5}  {:4}  {:11}  {text}", "", "")?;
+            continue;
+        }
+
+        let statement = match text.trim_start().split_once(':') {
+            Some((label, rest)) if !label.contains(char::is_whitespace) => rest.trim_start(),
+            _ => text.trim_start(),
+        };
+        if statement.starts_with('#') {
+            let (address, offset, _, _) = spans[0];
+            let end = spans
+                .iter()
+                .map(|&(_, offset, size, _)| offset + size)
+                .max()
+                .unwrap_or(offset);
+            let bytes = &binary[offset..end];
+
+            if bytes.is_empty() {
+                writeln!(listing, "{line:5}  {address:04x}  {:11}  {text}", "")?;
+            }
+            for (row, chunk) in bytes.chunks(LISTING_BYTES_PER_ROW).enumerate() {
+                let address = address.wrapping_add((row * LISTING_BYTES_PER_ROW) as u16);
+                if row == 0 {
+                    writeln!(
+                        listing,
+                        "{line:5}  {address:04x}  {:11}  {text}",
+                        hex_bytes(chunk)
+                    )?;
+                } else {
+                    writeln!(listing, "       {address:04x}  {}", hex_bytes(chunk))?;
+                }
+            }
+            continue;
+        }
+
+        for (position, &(address, offset, size, start)) in spans.iter().enumerate() {
+            let text = if position == 0 { text } else { "" };
+            let bytes = &binary[offset..offset + size];
+
+            if size < 2 {
+                writeln!(
+                    listing,
+                    "{line:5}  {address:04x}  {:11}  {text}",
+                    hex_bytes(bytes)
+                )?;
+                continue;
+            }
+
+            let words = disassemble(address, bytes);
+            let mnemonic = source[start..].split_whitespace().next().unwrap_or("");
+            let expanded = words.len() > 1
+                || !words[0]
+                    .2
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+                    .eq_ignore_ascii_case(mnemonic);
+
+            if expanded {
+                writeln!(listing, "{line:5}  {address:04x}  {:11}  {text}", "")?;
+                for (address, chunk, assembly) in words {
+                    writeln!(
+                        listing,
+                        "       {address:04x}  {:11}  + {assembly}",
+                        hex_bytes(chunk)
+                    )?;
+                }
+            } else {
+                writeln!(
+                    listing,
+                    "{line:5}  {address:04x}  {:11}  {text}",
+                    hex_bytes(bytes)
+                )?;
+            }
+        }
+    }
+
+    Ok(listing)
+}
+
+fn disassemble(address: u16, bytes: &[u8]) -> Vec<(u16, &[u8], String)> {
+    bytes
+        .chunks(2)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let address = address.wrapping_add((index * 2) as u16);
+            let assembly = match *chunk {
+                [low, high] => {
+                    let word = u16::from_le_bytes([low, high]);
+                    match Cpu::decode(word) {
+                        Ok(instruction) => instruction.to_assembly(address),
+                        Err(_) => format!("#d16 0x{word:04x}"),
+                    }
+                }
+                _ => format!("#d8 0x{:02x}", chunk[0]),
+            };
+            (address, chunk, assembly)
+        })
+        .collect()
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn assemble_to_file(
+    input: String,
+    output: String,
+    format: OutputFormat,
+    listing: Option<String>,
+    object: bool,
+    options: &AssemblerOptions,
+) -> Result<()> {
+    if object {
+        fs::write(
+            output,
+            assemble_to_object(input.as_str(), options)?.to_bytes(),
+        )?;
+        if let Some(listing) = listing {
+            fs::write(listing, assemble_to_listing(input.as_str(), options)?)?;
+        }
+        return Ok(());
+    }
+
+    let mut assembly = assemble(input.as_str(), options)?;
+    if let Some(listing) = listing {
+        fs::write(listing, format_listing(&mut assembly, input.as_str())?)?;
+    }
+    let executable = format_executable(&mut assembly);
+    fs::write(output, write_image(&executable, &format))?;
     Ok(())
 }
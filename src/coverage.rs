@@ -0,0 +1,200 @@
+use crate::executable::Executable;
+use crate::instructions::Instruction;
+use crate::layout::Layout;
+use crate::profile::Profile;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Default)]
+struct Line {
+    hits: u64,
+    branches: Vec<(u64, u64)>,
+}
+
+#[derive(Default)]
+pub struct Coverage {
+    files: BTreeMap<String, BTreeMap<u32, Line>>,
+}
+
+struct Totals {
+    lines: usize,
+    lines_hit: usize,
+    branches: usize,
+    branches_hit: usize,
+}
+
+impl Totals {
+    fn of<'a>(lines: impl Iterator<Item = &'a Line>) -> Totals {
+        let mut totals = Totals {
+            lines: 0,
+            lines_hit: 0,
+            branches: 0,
+            branches_hit: 0,
+        };
+        for line in lines {
+            totals.lines += 1;
+            totals.lines_hit += (line.hits > 0) as usize;
+            for &(taken, not_taken) in &line.branches {
+                totals.branches += 2;
+                totals.branches_hit += (taken > 0) as usize + (not_taken > 0) as usize;
+            }
+        }
+        totals
+    }
+
+    fn format(&self) -> String {
+        let percent = |hit: usize, total: usize| hit as f64 * 100.0 / total.max(1) as f64;
+        format!(
+            "lines {}/{} ({:.1}%), branches {}/{} ({:.1}%)",
+            self.lines_hit,
+            self.lines,
+            percent(self.lines_hit, self.lines),
+            self.branches_hit,
+            self.branches,
+            percent(self.branches_hit, self.branches)
+        )
+    }
+}
+
+fn ranges(lines: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Coverage {
+    pub fn add(&mut self, executable: &Executable, profile: &Profile, layout: &Layout) {
+        for entry in &executable.lines {
+            let file = &executable.files[entry.file as usize];
+            if file.starts_with("<std>")
+                || !layout
+                    .banks
+                    .iter()
+                    .any(|bank| bank.contains(entry.address) && bank.is_executable())
+            {
+                continue;
+            }
+            let Some(segment) = executable.segments.iter().find(|segment| {
+                (segment.address as usize..segment.address as usize + segment.data.len())
+                    .contains(&(entry.address as usize))
+            }) else {
+                continue;
+            };
+            let segment_end = segment.address as usize + segment.data.len();
+            let end = (entry.address as usize + entry.size as usize).min(segment_end);
+
+            let line = self
+                .files
+                .entry(file.clone())
+                .or_default()
+                .entry(entry.line)
+                .or_default();
+            line.hits += profile.counts.get(&entry.address).copied().unwrap_or(0);
+
+            let mut branch = 0;
+            for address in (entry.address as usize..end).step_by(2) {
+                let offset = address - segment.address as usize;
+                let Some(bytes) = segment.data.get(offset..offset + 2) else {
+                    break;
+                };
+                let word = u16::from_le_bytes([bytes[0], bytes[1]]);
+                if Instruction::decode(word)
+                    .is_none_or(|instruction| instruction.class() != "branch")
+                {
+                    continue;
+                }
+                if line.branches.len() == branch {
+                    line.branches.push((0, 0));
+                }
+                if let Some(&(taken, not_taken)) = profile.branches.get(&(address as u16)) {
+                    line.branches[branch].0 += taken;
+                    line.branches[branch].1 += not_taken;
+                }
+                branch += 1;
+            }
+        }
+    }
+
+    pub fn lcov(&self) -> String {
+        let mut result = String::new();
+        for (file, lines) in &self.files {
+            let _ = writeln!(result, "TN:\nSF:{file}");
+            for (number, line) in lines {
+                for (block, &(taken, not_taken)) in line.branches.iter().enumerate() {
+                    for (branch, count) in [taken, not_taken].into_iter().enumerate() {
+                        let count = if line.hits == 0 {
+                            String::from("-")
+                        } else {
+                            count.to_string()
+                        };
+                        let _ = writeln!(result, "BRDA:{number},{block},{branch},{count}");
+                    }
+                }
+            }
+            for (number, line) in lines {
+                let _ = writeln!(result, "DA:{number},{}", line.hits);
+            }
+            let totals = Totals::of(lines.values());
+            let _ = writeln!(result, "BRF:{}", totals.branches);
+            let _ = writeln!(result, "BRH:{}", totals.branches_hit);
+            let _ = writeln!(result, "LF:{}", totals.lines);
+            let _ = writeln!(result, "LH:{}", totals.lines_hit);
+            result.push_str("end_of_record\n");
+        }
+        result
+    }
+
+    pub fn summary(&self) -> String {
+        let mut result = String::new();
+        for (file, lines) in &self.files {
+            let _ = writeln!(result, "{file}: {}", Totals::of(lines.values()).format());
+            let missed = lines
+                .iter()
+                .filter(|(_, line)| line.hits == 0)
+                .map(|(&number, _)| number)
+                .collect::<Vec<_>>();
+            if !missed.is_empty() {
+                let _ = writeln!(result, "    not executed: {}", ranges(&missed));
+            }
+            let partial = lines
+                .iter()
+                .filter(|(_, line)| {
+                    line.hits > 0
+                        && line
+                            .branches
+                            .iter()
+                            .any(|&(taken, not_taken)| taken == 0 || not_taken == 0)
+                })
+                .map(|(&number, _)| number)
+                .collect::<Vec<_>>();
+            if !partial.is_empty() {
+                let _ = writeln!(
+                    result,
+                    "    branches not taken both ways: {}",
+                    ranges(&partial)
+                );
+            }
+        }
+        let _ = writeln!(
+            result,
+            "total: {}",
+            Totals::of(self.files.values().flat_map(|lines| lines.values())).format()
+        );
+        result
+    }
+}
@@ -0,0 +1,126 @@
+use crate::emulator::{Cpu, Memory, MemoryFormat, print_memory};
+use crate::executable::Symbol;
+use crate::utils::parse_u16;
+use anyhow::{Context, Result, bail};
+use std::io::{BufRead, Write, stdin, stdout};
+
+pub const HISTORY: usize = 1 << 16;
+
+const HELP: &str = "\
+step [n]                  execute n instructions
+back [n]                  undo n instructions
+continue                  run until HLT or a fault
+reverse-continue <addr>   go back to the last write of a byte
+regs                      show registers and flags
+mem <addr> [count]        show memory
+quit                      stop the program";
+
+fn parse_address(input: &str, symbols: &[Symbol]) -> Result<u16> {
+    match symbols.iter().find(|symbol| symbol.name == input) {
+        Some(symbol) => Ok(symbol.address),
+        None => parse_u16(input).with_context(|| format!("unknown address {input}")),
+    }
+}
+
+fn parse_count(input: Option<&str>) -> Result<usize> {
+    match input {
+        Some(count) => Ok(count.parse().context("invalid count")?),
+        None => Ok(1),
+    }
+}
+
+fn location(cpu: &Cpu, symbols: &[Symbol]) -> String {
+    let address = cpu.program_counter;
+    let label = match symbols.iter().find(|symbol| symbol.address == address) {
+        Some(symbol) => format!(" <{}>", symbol.name),
+        None => String::new(),
+    };
+    let instruction = match Cpu::decode(cpu.read_word(address)) {
+        Ok(instruction) => instruction.to_assembly(address),
+        Err(error) => error.to_string(),
+    };
+    format!("0x{address:04x}{label}: {instruction}")
+}
+
+fn step(cpu: &mut Cpu) -> Result<bool> {
+    if cpu.halted {
+        return Ok(false);
+    }
+    cpu.step()?;
+    Ok(!cpu.halted)
+}
+
+fn execute(cpu: &mut Cpu, symbols: &[Symbol], command: &str, args: &[&str]) -> Result<()> {
+    match (command, args) {
+        ("s" | "step", [] | [_]) => {
+            for _ in 0..parse_count(args.first().copied())? {
+                if !step(cpu)? {
+                    break;
+                }
+            }
+        }
+        ("c" | "continue", []) => while step(cpu)? {},
+        ("b" | "back", [] | [_]) => {
+            for _ in 0..parse_count(args.first().copied())? {
+                if !cpu.step_back() {
+                    println!("no more history");
+                    break;
+                }
+            }
+        }
+        ("rc" | "reverse-continue", [address]) => {
+            let address = parse_address(address, symbols)?;
+            if cpu.reverse_continue(address).is_none() {
+                println!(
+                    "no recorded write to 0x{address:04x}, stopped at the start of the history"
+                );
+            }
+        }
+        ("r" | "regs", []) => {
+            println!("{:?}", cpu.registers);
+            println!("{:?}", cpu.flags);
+            println!("cycles: {}", cpu.cycles);
+        }
+        ("m" | "mem", [address] | [address, _]) => {
+            let start = parse_address(address, symbols)? as usize;
+            let end = (start + parse_count(args.get(1).copied()).unwrap_or(16)).min(0x10000);
+            print_memory(
+                &cpu.memory,
+                &cpu.memory,
+                (start, end),
+                &MemoryFormat::Hex,
+                false,
+            );
+        }
+        ("h" | "help", []) => println!("{HELP}"),
+        _ => bail!("invalid command, type help for a list of commands"),
+    }
+    Ok(())
+}
+
+pub fn debug(cpu: &mut Cpu, symbols: &[Symbol]) -> Result<&'static str> {
+    let mut lines = stdin().lock().lines();
+    loop {
+        println!("{}", location(cpu, symbols));
+        if cpu.halted {
+            println!("halted after {} cycles", cpu.cycles);
+        }
+        print!("> ");
+        stdout().flush()?;
+        let Some(line) = lines.next() else {
+            return Ok("quit");
+        };
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        if matches!(command, "q" | "quit") {
+            return Ok(if cpu.halted { "halt" } else { "quit" });
+        }
+        let args = words.collect::<Vec<_>>();
+        if let Err(error) = execute(cpu, symbols, command, &args) {
+            println!("error: {error:#}");
+        }
+    }
+}
@@ -1,9 +1,21 @@
-use crate::assembler::assemble_to_binary;
+use crate::assembler::{AssemblerOptions, assemble_to_executable};
+use crate::debugger::{HISTORY, debug};
+use crate::executable::{Executable, Symbol};
+use crate::formats::{OutputFormat, Segment, read_image};
 use crate::instructions::Instruction;
-use anyhow::{Result, bail};
-use std::{fs::read, str::FromStr};
+use crate::layout::{Bank, Layout};
+use crate::pipeline::{Pipeline, Stats};
+use crate::profile::Profile;
+use crate::snapshot::Snapshot;
+use crate::timing::Timing;
+use crate::utils::parse_u16;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::IsTerminal;
+use std::{fs, fs::read, path::Path, str::FromStr};
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
 pub struct Flags {
     pub carry: bool,
     pub overflow: bool,
@@ -17,6 +29,71 @@ pub struct Cpu {
     pub flags: Flags,
     pub program_counter: u16,
     pub halted: bool,
+    pub cycles: u64,
+    pub timing: Timing,
+    pub banks: Vec<Bank>,
+    pub protect: bool,
+    pub abi_check: Option<AbiCheck>,
+    pub history: Option<History>,
+    pub profile: Option<Profile>,
+    fault: Option<String>,
+}
+
+const CALLEE_SAVED: [usize; 2] = [4, 7];
+
+#[derive(Clone)]
+struct Frame {
+    call: u16,
+    target: u16,
+    return_address: u16,
+    saved: [u16; CALLEE_SAVED.len()],
+}
+
+#[derive(Serialize)]
+pub struct Violation {
+    pub call: u16,
+    pub target: u16,
+    pub register: usize,
+    pub before: u16,
+    pub after: u16,
+}
+
+struct Undo {
+    registers: [u16; 8],
+    flags: Flags,
+    program_counter: u16,
+    halted: bool,
+    cycles: u64,
+    memory: Vec<(u16, u8)>,
+    frames: Option<(Vec<Frame>, usize)>,
+}
+
+pub struct History {
+    records: VecDeque<Undo>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> History {
+        History {
+            records: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct AbiCheck {
+    frames: Vec<Frame>,
+    pub violations: Vec<Violation>,
 }
 
 pub struct EmulatorOptions {
@@ -25,15 +102,75 @@ pub struct EmulatorOptions {
     pub show_registers: bool,
     pub show_flags: bool,
     pub show_memory: bool,
-    pub memory_start: u16,
-    pub memory_end: u16,
+    pub memory_start: Option<u16>,
+    pub memory_end: Option<u16>,
     pub memory_format: MemoryFormat,
+    pub dumps: Vec<Dump>,
+    pub entry: Option<u16>,
+    pub check_abi: bool,
+    pub protect: bool,
+    pub output: ResultFormat,
+    pub save_state: Option<String>,
+    pub save_at: Option<u64>,
+    pub load_state: Option<String>,
+    pub timing: Timing,
+    pub engine: Engine,
+    pub profile: bool,
+    pub folded: Option<String>,
+    pub assembler: AssemblerOptions,
+}
+
+#[derive(Clone)]
+pub struct Load {
+    pub path: String,
+    pub address: u16,
+}
+
+impl FromStr for Load {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.rsplit_once('@') {
+            Some((path, address)) => Ok(Load {
+                path: path.to_string(),
+                address: parse_u16(address).context("invalid load address")?,
+            }),
+            None => Ok(Load {
+                path: s.to_string(),
+                address: 0,
+            }),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub enum InputFormat {
     Asm,
-    Bin,
+    Image(OutputFormat),
+}
+
+impl InputFormat {
+    pub fn from_path(path: &str) -> InputFormat {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("");
+        match extension {
+            "asm" => InputFormat::Asm,
+            "hex" | "ihex" => InputFormat::Image(OutputFormat::Ihex),
+            "srec" | "s19" => InputFormat::Image(OutputFormat::Srec),
+            "mem" | "memh" => InputFormat::Image(OutputFormat::Memh),
+            "logisim" => InputFormat::Image(OutputFormat::Logisim),
+            "rx" => InputFormat::Image(OutputFormat::Rx),
+            _ => InputFormat::Image(OutputFormat::Bin),
+        }
+    }
+
+    pub fn load(&self, path: &str, options: &AssemblerOptions) -> Result<Executable> {
+        match self {
+            InputFormat::Asm => assemble_to_executable(path, options),
+            InputFormat::Image(format) => read_image(&read(path)?, format),
+        }
+    }
 }
 
 impl FromStr for InputFormat {
@@ -41,8 +178,9 @@ impl FromStr for InputFormat {
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s {
             "asm" => Ok(InputFormat::Asm),
-            "bin" => Ok(InputFormat::Bin),
-            _ => anyhow::bail!("invalid input format"),
+            _ => Ok(InputFormat::Image(
+                s.parse().context("invalid input format")?,
+            )),
         }
     }
 }
@@ -52,6 +190,43 @@ pub enum MemoryFormat {
     Hex,
     Dec,
     Bin,
+    U16,
+    I16,
+}
+
+#[derive(Clone)]
+pub enum Dump {
+    Range { start: u16, end: u16 },
+    Bank(String),
+}
+
+impl FromStr for Dump {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((start, end)) => {
+                let start = parse_u16(start).context("invalid dump start")?;
+                let end = parse_u16(end).context("invalid dump end")?;
+                if end < start {
+                    anyhow::bail!("dump ends before it starts");
+                }
+                Ok(Dump::Range { start, end })
+            }
+            None => Ok(Dump::Bank(s.to_string())),
+        }
+    }
+}
+
+impl Dump {
+    fn resolve(&self, layout: &Layout) -> Result<(usize, usize)> {
+        match self {
+            Dump::Range { start, end } => Ok((*start as usize, *end as usize + 1)),
+            Dump::Bank(name) => match layout.bank(name) {
+                Some(bank) => Ok((bank.address as usize, bank.end())),
+                None => bail!("unknown bank {name}"),
+            },
+        }
+    }
 }
 
 impl FromStr for MemoryFormat {
@@ -61,11 +236,68 @@ impl FromStr for MemoryFormat {
             "hex" => Ok(MemoryFormat::Hex),
             "dec" => Ok(MemoryFormat::Dec),
             "bin" => Ok(MemoryFormat::Bin),
+            "u16" => Ok(MemoryFormat::U16),
+            "i16" => Ok(MemoryFormat::I16),
             _ => anyhow::bail!("invalid input format"),
         }
     }
 }
 
+#[derive(Clone)]
+pub enum Engine {
+    Interpreter,
+    Pipeline,
+}
+
+impl FromStr for Engine {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "interpreter" => Ok(Engine::Interpreter),
+            "pipeline" => Ok(Engine::Pipeline),
+            _ => anyhow::bail!("invalid engine"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum ResultFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for ResultFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ResultFormat::Text),
+            "json" => Ok(ResultFormat::Json),
+            _ => anyhow::bail!("invalid output format"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MemoryRange<'a> {
+    start: usize,
+    end: usize,
+    bytes: &'a [u8],
+}
+
+#[derive(Serialize)]
+struct State<'a> {
+    registers: [u16; 8],
+    pc: u16,
+    flags: &'a Flags,
+    halt: &'static str,
+    error: Option<String>,
+    cycles: u64,
+    memory: Vec<MemoryRange<'a>>,
+    violations: &'a [Violation],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pipeline: Option<&'a Stats>,
+}
+
 impl Default for Cpu {
     fn default() -> Self {
         Cpu {
@@ -74,142 +306,218 @@ impl Default for Cpu {
             flags: Flags::default(),
             program_counter: 0,
             halted: false,
+            cycles: 0,
+            timing: Timing::default(),
+            banks: Vec::new(),
+            protect: false,
+            abi_check: None,
+            history: None,
+            profile: None,
+            fault: None,
         }
     }
 }
 
 impl Cpu {
+    pub fn load(&mut self, segments: &[Segment], offset: u16) -> Result<()> {
+        for segment in segments {
+            let start = segment.address as usize + offset as usize;
+            let end = start + segment.data.len();
+            if end > self.memory.len() {
+                bail!("segment at 0x{start:04x} does not fit in memory");
+            }
+            self.memory[start..end].copy_from_slice(&segment.data);
+        }
+        Ok(())
+    }
+
+    pub fn with_layout(layout: &Layout) -> Cpu {
+        let mut cpu = Cpu {
+            banks: layout.banks.clone(),
+            ..Cpu::default()
+        };
+        if let Some(stack) = layout.bank("stack") {
+            cpu.registers[7] = stack.end() as u16;
+        }
+        cpu
+    }
+
+    fn bank_at(&self, address: u16) -> Option<&Bank> {
+        self.banks.iter().find(|bank| bank.contains(address))
+    }
+
+    pub(crate) fn fetch_fault(&self, address: u16) -> Option<String> {
+        if self.protect && !self.bank_at(address).is_some_and(Bank::is_executable) {
+            return Some(format!("execution outside of code at 0x{address:04x}"));
+        }
+        None
+    }
+
+    pub(crate) fn write_fault(&self, address: u16) -> Option<String> {
+        if !self.protect {
+            return None;
+        }
+        match self.bank_at(address) {
+            Some(bank) if bank.is_writable() => None,
+            Some(bank) => Some(format!(
+                "write to read-only bank {} at 0x{address:04x}",
+                bank.name
+            )),
+            None => Some(format!("write to unmapped address 0x{address:04x}")),
+        }
+    }
+
     pub fn run(&mut self) -> Result<()> {
         while !self.halted {
-            let instruction = self.fetch();
-            let instruction = self.decode(instruction)?;
-            self.execute(instruction);
+            self.step()?;
         }
         Ok(())
     }
 
-    fn fetch(&mut self) -> u16 {
-        let instruction = self.read_word(self.program_counter);
-        self.program_counter = self.program_counter.wrapping_add(2);
-        instruction
-    }
-
-    fn decode(&self, instruction: u16) -> Result<Instruction> {
-        match instruction >> 12 {
-            0x0 => {
-                let rd = ((instruction >> 9) & 0b111) as usize;
-                let ra = ((instruction >> 6) & 0b111) as usize;
-                let rb = ((instruction >> 3) & 0b111) as usize;
-                match instruction & 0b111 {
-                    0b000 => Ok(Instruction::Add { rd, ra, rb }),
-                    0b001 => Ok(Instruction::AddWithCarry { rd, ra, rb }),
-                    0b010 => Ok(Instruction::Subtract { rd, ra, rb }),
-                    0b011 => Ok(Instruction::SubtractWithBorrow { rd, ra, rb }),
-                    0b100 => Ok(Instruction::And { rd, ra, rb }),
-                    0b101 => Ok(Instruction::Or { rd, ra, rb }),
-                    0b110 => Ok(Instruction::Xor { rd, ra, rb }),
-                    0b111 => Ok(Instruction::Not { rd, ra }),
-                    _ => unreachable!(),
-                }
-            }
-            0x1 => {
-                let rd = ((instruction >> 9) & 0b111) as usize;
-                let rs = ((instruction >> 6) & 0b111) as usize;
-                let imm = (instruction >> 2) & 0b1111;
-                match instruction & 0b11 {
-                    0b00 => Ok(Instruction::LogicalLeftShift { rd, rs, imm }),
-                    0b01 => Ok(Instruction::LogicalRightShift { rd, rs, imm }),
-                    0b10 => Ok(Instruction::ArithmeticRightShift { rd, rs, imm }),
-                    0b11 => Ok(Instruction::RotateRight { rd, rs, imm }),
-                    _ => unreachable!(),
-                }
-            }
-            0x2 => {
-                let rd: usize = ((instruction >> 9) & 0b111) as usize;
-                let imm = (instruction >> 1) & 0b1111_1111;
-                match instruction & 0b1 {
-                    0b0 => Ok(Instruction::AddImmediate { rd, imm }),
-                    0b1 => Ok(Instruction::SubImmediate { rd, imm }),
-                    _ => unreachable!(),
-                }
-            }
-            0x3 => {
-                let ra = ((instruction >> 9) & 0b111) as usize;
-                let rb = ((instruction >> 6) & 0b111) as usize;
-                match instruction & 0b11 {
-                    0b00 => Ok(Instruction::Compare { ra, rb }),
-                    0b01 => bail!("Unknown instruction: 0x{instruction:04x}"),
-                    0b10 => Ok(Instruction::CompareLowBytes { ra, rb }),
-                    0b11 => Ok(Instruction::CompareHighBytes { ra, rb }),
-                    _ => unreachable!(),
-                }
-            }
-            0x4 => {
-                let rs: usize = ((instruction >> 9) & 0b111) as usize;
-                let imm = (instruction >> 1) & 0b1111_1111;
-                match instruction & 0b1 {
-                    0b0 => Ok(Instruction::CompareImmediateWithLowByte { rs, imm }),
-                    0b1 => Ok(Instruction::CompareImmediateWithHighByte { rs, imm }),
-                    _ => unreachable!(),
-                }
-            }
-            0x5 => {
-                let rd: usize = ((instruction >> 9) & 0b111) as usize;
-                let imm = (instruction >> 1) & 0b1111_1111;
-                match instruction & 0b1 {
-                    0b0 => Ok(Instruction::MoveImmediateToLowByte { rd, imm }),
-                    0b1 => Ok(Instruction::MoveImmediateToHighByte { rd, imm }),
-                    _ => unreachable!(),
-                }
+    pub fn step(&mut self) -> Result<()> {
+        let address = self.program_counter;
+        if let Some(fault) = self.fetch_fault(address) {
+            bail!(fault);
+        }
+        let instruction = Self::decode(self.read_word(address))?;
+        if let Some(history) = &mut self.history {
+            if history.records.len() == history.capacity {
+                history.records.pop_front();
             }
-            0x6 => {
-                let rd = ((instruction >> 9) & 0b111) as usize;
-                let rs = ((instruction >> 6) & 0b111) as usize;
-                match instruction & 0b11 {
-                    0b00 => Ok(Instruction::LoadWord { rd, rs }),
-                    0b01 => bail!("Unknown instruction: 0x{instruction:04x}"),
-                    0b10 => Ok(Instruction::LoadToLowByte { rd, rs }),
-                    0b11 => Ok(Instruction::LoadToHighByte { rd, rs }),
-                    _ => unreachable!(),
-                }
+            history.records.push_back(Undo {
+                registers: self.registers,
+                flags: self.flags.clone(),
+                program_counter: self.program_counter,
+                halted: self.halted,
+                cycles: self.cycles,
+                memory: Vec::new(),
+                frames: self
+                    .abi_check
+                    .as_ref()
+                    .map(|check| (check.frames.clone(), check.violations.len())),
+            });
+        }
+        self.program_counter = address.wrapping_add(2);
+        self.execute(instruction);
+        if let Some(fault) = self.fault.take() {
+            self.program_counter = address;
+            if let Some(history) = &mut self.history {
+                history.records.pop_back();
             }
-            0x7 => {
-                let rs = ((instruction >> 9) & 0b111) as usize;
-                let rd = ((instruction >> 6) & 0b111) as usize;
-                match instruction & 0b11 {
-                    0b00 => Ok(Instruction::StoreWord { rs, rd }),
-                    0b01 => bail!("Unknown instruction: 0x{instruction:04x}"),
-                    0b10 => Ok(Instruction::StoreFromLowByte { rs, rd }),
-                    0b11 => Ok(Instruction::StoreFromHighByte { rs, rd }),
-                    _ => unreachable!(),
-                }
+            bail!(fault);
+        }
+        let taken = self.program_counter != address.wrapping_add(2);
+        let cycles = self.timing.cycles(&instruction, taken);
+        self.cycles += cycles;
+        self.retire(address, instruction, cycles);
+        Ok(())
+    }
+
+    pub(crate) fn retire(&mut self, address: u16, instruction: Instruction, cycles: u64) {
+        let is_call = matches!(
+            instruction,
+            Instruction::JumpToOffset { .. } | Instruction::JumpToPointer { rs: 0..=5 | 7 }
+        ) && self.registers[6] == address.wrapping_add(2);
+        let is_return = matches!(instruction, Instruction::JumpToPointer { rs: 6 });
+        if let Some(profile) = &mut self.profile {
+            profile.record(
+                address,
+                instruction,
+                cycles,
+                self.program_counter,
+                is_call,
+                is_return,
+            );
+        }
+        if is_call {
+            self.enter_call(address);
+        } else if is_return {
+            self.leave_call();
+        }
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        let Some(undo) = self
+            .history
+            .as_mut()
+            .and_then(|history| history.records.pop_back())
+        else {
+            return false;
+        };
+        for &(address, value) in undo.memory.iter().rev() {
+            self.memory[address as usize] = value;
+        }
+        self.registers = undo.registers;
+        self.flags = undo.flags;
+        self.program_counter = undo.program_counter;
+        self.halted = undo.halted;
+        self.cycles = undo.cycles;
+        if let (Some(check), Some((frames, violations))) = (&mut self.abi_check, undo.frames) {
+            check.frames = frames;
+            check.violations.truncate(violations);
+        }
+        true
+    }
+
+    pub fn reverse_continue(&mut self, address: u16) -> Option<u16> {
+        loop {
+            let wrote = self
+                .history
+                .as_ref()?
+                .records
+                .back()?
+                .memory
+                .iter()
+                .any(|&(written, _)| written == address);
+            self.step_back();
+            if wrote {
+                return Some(self.program_counter);
             }
-            0x8 => {
-                let offset = instruction & 0b1111_1111_1111;
-                let offset = ((offset << 4) as i16) >> 4;
-                Ok(Instruction::JumpToOffset { offset })
-            }
-            0x9 => {
-                let rs = ((instruction >> 9) & 0b111) as usize;
-                Ok(Instruction::JumpToPointer { rs })
-            }
-            0xa => {
-                let offset = (instruction >> 3) & 0b1_1111_1111;
-                let offset = ((offset << 7) as i16) >> 7;
-                match instruction & 0b111 {
-                    0b000 => Ok(Instruction::BranchIfCarry { offset }),
-                    0b001 => Ok(Instruction::BranchIfNotCarry { offset }),
-                    0b010 => Ok(Instruction::BranchIfOverflow { offset }),
-                    0b011 => Ok(Instruction::BranchIfNotOverflow { offset }),
-                    0b100 => Ok(Instruction::BranchIfZero { offset }),
-                    0b101 => Ok(Instruction::BranchIfNotZero { offset }),
-                    0b110 => Ok(Instruction::BranchIfSigned { offset }),
-                    0b111 => Ok(Instruction::BranchIfNotSigned { offset }),
-                    _ => unreachable!(),
-                }
+        }
+    }
+
+    fn enter_call(&mut self, call: u16) {
+        if let Some(check) = &mut self.abi_check {
+            check.frames.push(Frame {
+                call,
+                target: self.program_counter,
+                return_address: call.wrapping_add(2),
+                saved: CALLEE_SAVED.map(|register| self.registers[register]),
+            });
+        }
+    }
+
+    fn leave_call(&mut self) {
+        let Some(check) = &mut self.abi_check else {
+            return;
+        };
+        let Some(index) = check
+            .frames
+            .iter()
+            .rposition(|frame| frame.return_address == self.program_counter)
+        else {
+            return;
+        };
+        let frame = check.frames.remove(index);
+        check.frames.truncate(index);
+        for (register, before) in CALLEE_SAVED.into_iter().zip(frame.saved) {
+            let after = self.registers[register];
+            if after != before {
+                check.violations.push(Violation {
+                    call: frame.call,
+                    target: frame.target,
+                    register,
+                    before,
+                    after,
+                });
             }
-            0xf => Ok(Instruction::Halt),
-            _ => bail!("Unknown instruction: 0x{instruction:04x}"),
+        }
+    }
+
+    pub fn decode(instruction: u16) -> Result<Instruction> {
+        match Instruction::decode(instruction) {
+            Some(instruction) => Ok(instruction),
+            None => bail!("Unknown instruction: 0x{instruction:04x}"),
         }
     }
 
@@ -309,7 +617,7 @@ impl Cpu {
                 let rs = self.registers[rs];
                 let result = rs << imm;
                 self.registers[rd] = result;
-                self.flags.carry = ((rs >> (16 - imm)) & 1) != 0;
+                self.flags.carry = imm != 0 && ((rs >> (16 - imm)) & 1) != 0;
                 self.flags.overflow = false;
                 self.flags.zero = result == 0;
                 self.flags.signed = (result & 0x8000) != 0;
@@ -318,7 +626,7 @@ impl Cpu {
                 let rs = self.registers[rs];
                 let result = rs >> imm;
                 self.registers[rd] = result;
-                self.flags.carry = ((rs >> (imm - 1)) & 1) != 0;
+                self.flags.carry = imm != 0 && ((rs >> (imm - 1)) & 1) != 0;
                 self.flags.overflow = false;
                 self.flags.zero = result == 0;
                 self.flags.signed = (result & 0x8000) != 0;
@@ -327,16 +635,16 @@ impl Cpu {
                 let rs = self.registers[rs] as i16;
                 let result = (rs >> imm) as u16;
                 self.registers[rd] = result;
-                self.flags.carry = (((rs as u16) >> (imm - 1)) & 1) != 0;
+                self.flags.carry = imm != 0 && (((rs as u16) >> (imm - 1)) & 1) != 0;
                 self.flags.overflow = false;
                 self.flags.zero = result == 0;
                 self.flags.signed = (result & 0x8000) != 0;
             }
             Instruction::RotateRight { rd, rs, imm } => {
                 let rs = self.registers[rs];
-                let result = (rs >> imm) | (rs << (16 - imm));
+                let result = rs.rotate_right(imm as u32);
                 self.registers[rd] = result;
-                self.flags.carry = (result >> 15) & 1 != 0;
+                self.flags.carry = imm != 0 && (result >> 15) & 1 != 0;
                 self.flags.overflow = false;
                 self.flags.zero = result == 0;
                 self.flags.signed = (result & 0x8000) != 0;
@@ -522,17 +830,292 @@ impl Memory for Cpu {
         self.memory[address as usize]
     }
 
+    fn write_word(&mut self, address: u16, value: u16) {
+        if let Some(fault) = self
+            .write_fault(address)
+            .or_else(|| self.write_fault(address.wrapping_add(1)))
+        {
+            self.fault = Some(fault);
+            return;
+        }
+        self.write_byte(address, value as u8);
+        self.write_byte(address.wrapping_add(1), (value >> 8) as u8);
+    }
+
     fn write_byte(&mut self, address: u16, value: u8) {
+        if let Some(fault) = self.write_fault(address) {
+            self.fault = Some(fault);
+            return;
+        }
+        if let Some(undo) = self
+            .history
+            .as_mut()
+            .and_then(|history| history.records.back_mut())
+        {
+            undo.memory.push((address, self.memory[address as usize]));
+        }
         self.memory[address as usize] = value;
     }
 }
 
-pub fn emulate_binary(binary: Vec<u8>, options: EmulatorOptions) -> Result<()> {
-    let mut cpu = Cpu::default();
-    for (index, byte) in binary.iter().enumerate() {
-        cpu.write_byte(index as u16, *byte);
+pub fn emulate_executable(executable: &Executable, options: EmulatorOptions) -> Result<()> {
+    let mut cpu = Cpu::with_layout(&options.assembler.layout);
+    cpu.load(&executable.segments, 0)?;
+    cpu.program_counter = options.entry.unwrap_or(executable.entry);
+    emulate(cpu, options, &executable.symbols)
+}
+
+fn describe_address(address: u16, symbols: &[Symbol]) -> String {
+    match symbols.iter().find(|symbol| symbol.address == address) {
+        Some(symbol) => format!("{} (0x{address:04x})", symbol.name),
+        None => format!("0x{address:04x}"),
+    }
+}
+
+fn memory_ranges(options: &EmulatorOptions) -> Result<Vec<(usize, usize)>> {
+    let mut ranges = Vec::new();
+    if options.show_memory {
+        let data_start = options
+            .assembler
+            .layout
+            .bank("data")
+            .map_or(0, |bank| bank.address);
+        let start = options.memory_start.unwrap_or(data_start) as usize;
+        let end = options
+            .memory_end
+            .map_or(start + 0x10, |end| end as usize + 1)
+            .min(0x10000);
+        ranges.push((start, end.max(start)));
+    }
+    for dump in &options.dumps {
+        ranges.push(dump.resolve(&options.assembler.layout)?);
+    }
+    Ok(ranges)
+}
+
+pub fn print_memory(
+    memory: &[u8],
+    initial: &[u8],
+    (start, end): (usize, usize),
+    format: &MemoryFormat,
+    highlight: bool,
+) {
+    for row in (start..end).step_by(16) {
+        let row_end = (row + 16).min(end);
+        let mut line = format!("{row:04x}: ");
+        let mut cells = 0;
+        let mut address = row;
+        while address < row_end {
+            let size = match format {
+                MemoryFormat::U16 | MemoryFormat::I16 => (row_end - address).min(2),
+                _ => 1,
+            };
+            let bytes = &memory[address..address + size];
+            let word = u16::from_le_bytes([bytes[0], *bytes.get(1).unwrap_or(&0)]);
+            let cell = match format {
+                MemoryFormat::Hex => format!("{:02x}", bytes[0]),
+                MemoryFormat::Dec => format!("{:03}", bytes[0]),
+                MemoryFormat::Bin => format!("{:08b}", bytes[0]),
+                MemoryFormat::U16 => format!("{word:5}"),
+                MemoryFormat::I16 => format!("{:6}", word as i16),
+            };
+            if highlight && bytes != &initial[address..address + size] {
+                line.push_str(&format!("\x1b[1;33m{cell}\x1b[0m "));
+            } else {
+                line.push_str(&cell);
+                line.push(' ');
+            }
+            cells += cell.len() + 1;
+            address += size;
+        }
+        let width: usize = match format {
+            MemoryFormat::Hex => 3 * 16,
+            MemoryFormat::Dec => 4 * 16,
+            MemoryFormat::Bin => 9 * 16,
+            MemoryFormat::U16 => 6 * 8,
+            MemoryFormat::I16 => 7 * 8,
+        };
+        line.push_str(&" ".repeat(width.saturating_sub(cells)));
+        let ascii: String = memory[row..row_end]
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        println!("{line}|{ascii}|");
+    }
+}
+
+fn save_state(cpu: &Cpu, path: &str) -> Result<()> {
+    fs::write(path, Snapshot::capture(cpu).to_bytes())
+        .with_context(|| format!("unable to write {path}"))
+}
+
+fn emulate(mut cpu: Cpu, options: EmulatorOptions, symbols: &[Symbol]) -> Result<()> {
+    if options.check_abi {
+        cpu.abi_check = Some(AbiCheck::default());
+    }
+    cpu.protect = options.protect;
+    if options.profile || options.folded.is_some() {
+        cpu.profile = Some(Profile::default());
+    }
+    cpu.timing = options.timing.clone();
+    if let Some(path) = &options.load_state {
+        let bytes = read(path).with_context(|| format!("unable to read {path}"))?;
+        let snapshot =
+            Snapshot::from_bytes(&bytes).with_context(|| format!("invalid snapshot {path}"))?;
+        snapshot.restore(&mut cpu);
+    }
+    let limit = options.cycles.map(|limit| cpu.cycles + limit);
+    let initial = cpu.memory;
+    let ranges = memory_ranges(&options)?;
+
+    let mut pipeline = match options.engine {
+        Engine::Interpreter => None,
+        Engine::Pipeline if options.step || options.save_at.is_some() => {
+            bail!("the pipeline engine does not support --step or --save-at")
+        }
+        Engine::Pipeline => Some(Pipeline::new(&cpu)),
+    };
+    if options.step && (options.profile || options.folded.is_some()) {
+        bail!("the debugger does not support --profile or --folded");
+    }
+
+    let mut saved = false;
+    let mut error = None;
+    let halt = if options.step {
+        cpu.history = Some(History::new(HISTORY));
+        debug(&mut cpu, symbols)?
+    } else {
+        loop {
+            if let Some(path) = &options.save_state
+                && !saved
+                && options.save_at.is_some_and(|save_at| cpu.cycles >= save_at)
+            {
+                save_state(&cpu, path)?;
+                saved = true;
+            }
+            if cpu.halted
+                && pipeline
+                    .as_ref()
+                    .is_none_or(|pipeline| pipeline.is_done(&cpu))
+            {
+                break "halt";
+            }
+            if limit.is_some_and(|limit| cpu.cycles >= limit) {
+                break "cycle-limit";
+            }
+            let result = match &mut pipeline {
+                Some(pipeline) => pipeline.cycle(&mut cpu),
+                None => cpu.step(),
+            };
+            if let Err(fault) = result {
+                error = Some(fault);
+                break "fault";
+            }
+        }
+    };
+
+    if let Some(path) = &options.save_state
+        && error.is_none()
+    {
+        match options.save_at {
+            None => save_state(&cpu, path)?,
+            Some(save_at) if !saved => {
+                bail!(
+                    "stopped after {} cycles, before reaching cycle {save_at}",
+                    cpu.cycles
+                )
+            }
+            Some(_) => {}
+        }
+    }
+
+    if let Some(profile) = &cpu.profile {
+        if let Some(path) = &options.folded {
+            fs::write(path, profile.folded(symbols))
+                .with_context(|| format!("unable to write {path}"))?;
+        }
+        if options.profile {
+            let report = profile.report(symbols, &cpu.memory);
+            match options.output {
+                ResultFormat::Text => print!("{report}"),
+                ResultFormat::Json => eprint!("{report}"),
+            }
+        }
+    }
+
+    if let ResultFormat::Json = options.output {
+        let violations = cpu
+            .abi_check
+            .as_ref()
+            .map_or(&[][..], |check| &check.violations);
+        let state = State {
+            registers: cpu.registers,
+            pc: cpu.program_counter,
+            flags: &cpu.flags,
+            halt,
+            error: error.as_ref().map(|error| format!("{error:#}")),
+            cycles: cpu.cycles,
+            memory: ranges
+                .iter()
+                .map(|&(start, end)| MemoryRange {
+                    start,
+                    end: end.saturating_sub(1),
+                    bytes: &cpu.memory[start..end],
+                })
+                .collect(),
+            violations,
+            pipeline: pipeline.as_ref().map(|pipeline| &pipeline.stats),
+        };
+        println!("{}", serde_json::to_string_pretty(&state)?);
+        if let Some(error) = error {
+            return Err(error);
+        }
+        if !violations.is_empty() {
+            bail!("{} calling convention violations", violations.len());
+        }
+        return Ok(());
+    }
+
+    if let Some(error) = error {
+        return Err(error);
+    }
+    if halt == "cycle-limit" {
+        eprintln!("stopped after {} cycles", cpu.cycles);
+    }
+
+    if let Some(check) = &cpu.abi_check
+        && !check.violations.is_empty()
+    {
+        for violation in &check.violations {
+            eprintln!(
+                "r{} not preserved by call to {} from 0x{:04x}: 0x{:04x} -> 0x{:04x}",
+                violation.register,
+                describe_address(violation.target, symbols),
+                violation.call,
+                violation.before,
+                violation.after
+            );
+        }
+        bail!("{} calling convention violations", check.violations.len());
+    }
+
+    if let Some(pipeline) = &pipeline {
+        let stats = &pipeline.stats;
+        println!(
+            "pipeline: {} cycles, {} instructions, CPI {:.2}, {} stalls, {} flushed",
+            stats.cycles,
+            stats.instructions,
+            stats.cpi(),
+            stats.stalls,
+            stats.flushes
+        );
     }
-    cpu.run()?;
 
     if options.show_registers {
         println!("{:?}", cpu.registers);
@@ -542,24 +1125,18 @@ pub fn emulate_binary(binary: Vec<u8>, options: EmulatorOptions) -> Result<()> {
         println!("{:?}", cpu.flags);
     }
 
-    if options.show_memory {
-        let range_start = options.memory_start as usize;
-        let range_end = options.memory_end as usize;
-
-        let data = &cpu.memory[range_start..range_end];
-        for (i, chunk) in data.chunks(16).enumerate() {
-            let addr = range_start + i * 16;
-            print!("{:04x}: ", addr);
-
-            for byte in chunk {
-                match options.memory_format {
-                    MemoryFormat::Hex => print!("{:02x} ", byte),
-                    MemoryFormat::Dec => print!("{:03} ", byte),
-                    MemoryFormat::Bin => print!("{:08b} ", byte),
-                };
-            }
+    let highlight = std::io::stdout().is_terminal();
+    for (index, &range) in ranges.iter().enumerate() {
+        if index > 0 {
             println!();
         }
+        print_memory(
+            &cpu.memory,
+            &initial,
+            range,
+            &options.memory_format,
+            highlight,
+        );
     }
 
     Ok(())
@@ -568,16 +1145,20 @@ pub fn emulate_binary(binary: Vec<u8>, options: EmulatorOptions) -> Result<()> {
 pub fn emulate_file(
     input: String,
     input_format: InputFormat,
+    loads: Vec<Load>,
     options: EmulatorOptions,
 ) -> Result<()> {
-    match input_format {
-        InputFormat::Asm => {
-            let binary = assemble_to_binary(input.as_str())?;
-            emulate_binary(binary, options)
-        }
-        InputFormat::Bin => {
-            let binary = read(input.as_str())?;
-            emulate_binary(binary, options)
-        }
+    let executable = input_format.load(input.as_str(), &options.assembler)?;
+    let mut cpu = Cpu::with_layout(&options.assembler.layout);
+    cpu.load(&executable.segments, 0)?;
+
+    for load in loads {
+        let executable = InputFormat::from_path(load.path.as_str())
+            .load(load.path.as_str(), &options.assembler)?;
+        cpu.load(&executable.segments, load.address)
+            .with_context(|| format!("unable to load {}", load.path))?;
     }
+
+    cpu.program_counter = options.entry.unwrap_or(executable.entry);
+    emulate(cpu, options, &executable.symbols)
 }
@@ -0,0 +1,223 @@
+use crate::formats::Segment;
+use crate::utils::{Reader, write_string};
+use anyhow::{Result, bail};
+use std::fmt;
+use std::str::FromStr;
+
+pub const MAGIC: &[u8; 4] = b"RX16";
+pub const VERSION: u8 = 2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IsaProfile {
+    #[default]
+    Base,
+}
+
+impl IsaProfile {
+    fn id(&self) -> u8 {
+        match self {
+            IsaProfile::Base => 0,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<IsaProfile> {
+        match id {
+            0 => Ok(IsaProfile::Base),
+            _ => bail!("unknown ISA profile {id}"),
+        }
+    }
+}
+
+impl FromStr for IsaProfile {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "base" => Ok(IsaProfile::Base),
+            _ => anyhow::bail!("invalid ISA profile"),
+        }
+    }
+}
+
+impl fmt::Display for IsaProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IsaProfile::Base => write!(f, "base"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub address: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct LineEntry {
+    pub address: u16,
+    pub size: u16,
+    pub file: u16,
+    pub line: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Executable {
+    pub profile: IsaProfile,
+    pub entry: u16,
+    pub segments: Vec<Segment>,
+    pub symbols: Vec<Symbol>,
+    pub files: Vec<String>,
+    pub lines: Vec<LineEntry>,
+}
+
+impl Executable {
+    pub fn from_segments(segments: Vec<Segment>) -> Executable {
+        Executable {
+            segments,
+            ..Executable::default()
+        }
+    }
+
+    pub fn symbol(&self, name: &str) -> Option<u16> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.name == name)
+            .map(|symbol| symbol.address)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.profile.id());
+        bytes.extend_from_slice(&self.entry.to_le_bytes());
+        bytes.extend_from_slice(&(self.segments.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.symbols.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.files.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.lines.len() as u32).to_le_bytes());
+
+        for segment in &self.segments {
+            bytes.extend_from_slice(&segment.address.to_le_bytes());
+            bytes.extend_from_slice(&(segment.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&segment.data);
+        }
+
+        for symbol in &self.symbols {
+            bytes.extend_from_slice(&symbol.address.to_le_bytes());
+            write_string(&mut bytes, &symbol.name);
+        }
+
+        for file in &self.files {
+            write_string(&mut bytes, file);
+        }
+
+        for entry in &self.lines {
+            bytes.extend_from_slice(&entry.address.to_le_bytes());
+            bytes.extend_from_slice(&entry.size.to_le_bytes());
+            bytes.extend_from_slice(&entry.file.to_le_bytes());
+            bytes.extend_from_slice(&entry.line.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Executable> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            bail!("not a RISC16 executable");
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            bail!("unsupported executable version {version}");
+        }
+        let profile = IsaProfile::from_id(reader.u8()?)?;
+        let entry = reader.u16()?;
+        let segment_count = reader.u16()?;
+        let symbol_count = reader.u16()?;
+        let file_count = reader.u16()?;
+        let line_count = reader.u32()?;
+
+        let mut segments = Vec::new();
+        for _ in 0..segment_count {
+            let address = reader.u16()?;
+            let size = reader.u32()? as usize;
+            if address as usize + size > 0x10000 {
+                bail!("segment at 0x{address:04x} does not fit in memory");
+            }
+            let data = reader.take(size)?.to_vec();
+            segments.push(Segment { address, data });
+        }
+
+        let mut symbols = Vec::new();
+        for _ in 0..symbol_count {
+            let address = reader.u16()?;
+            let name = reader.string()?;
+            symbols.push(Symbol { name, address });
+        }
+
+        let mut files = Vec::new();
+        for _ in 0..file_count {
+            files.push(reader.string()?);
+        }
+
+        let mut lines = Vec::new();
+        for _ in 0..line_count {
+            let address = reader.u16()?;
+            let size = reader.u16()?;
+            let file = reader.u16()?;
+            let line = reader.u32()?;
+            lines.push(LineEntry {
+                address,
+                size,
+                file,
+                line,
+            });
+        }
+
+        Ok(Executable {
+            profile,
+            entry,
+            segments,
+            symbols,
+            files,
+            lines,
+        })
+    }
+}
+
+pub fn describe(executable: &Executable) -> String {
+    let mut result = String::new();
+
+    result.push_str(&format!("version:  {VERSION}\n"));
+    result.push_str(&format!("profile:  {}\n", executable.profile));
+    result.push_str(&format!("entry:    0x{:04x}\n", executable.entry));
+
+    result.push_str(&format!("\nsegments: {}\n", executable.segments.len()));
+    for segment in &executable.segments {
+        let end = segment.address as usize + segment.data.len();
+        result.push_str(&format!(
+            "  0x{:04x}..0x{:04x}  {} bytes\n",
+            segment.address,
+            end,
+            segment.data.len()
+        ));
+    }
+
+    result.push_str(&format!("\nsymbols:  {}\n", executable.symbols.len()));
+    for symbol in &executable.symbols {
+        result.push_str(&format!("  0x{:04x}  {}\n", symbol.address, symbol.name));
+    }
+
+    result.push_str(&format!(
+        "\nlines:    {} entries in {} files\n",
+        executable.lines.len(),
+        executable.files.len()
+    ));
+    for file in &executable.files {
+        result.push_str(&format!("  {file}\n"));
+    }
+
+    result
+}
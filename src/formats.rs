@@ -0,0 +1,372 @@
+use crate::executable::Executable;
+use anyhow::{Context, Result, bail};
+use std::fmt::Write;
+use std::str::FromStr;
+
+const IHEX_BYTES_PER_RECORD: usize = 16;
+const SREC_BYTES_PER_RECORD: usize = 16;
+const MEMH_WORDS_PER_LINE: usize = 8;
+const LOGISIM_WORDS_PER_LINE: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct Segment {
+    pub address: u16,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub enum OutputFormat {
+    Bin,
+    Ihex,
+    Srec,
+    Memh,
+    Logisim,
+    Rx,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "bin" => Ok(OutputFormat::Bin),
+            "ihex" | "hex" => Ok(OutputFormat::Ihex),
+            "srec" => Ok(OutputFormat::Srec),
+            "memh" => Ok(OutputFormat::Memh),
+            "logisim" => Ok(OutputFormat::Logisim),
+            "rx" => Ok(OutputFormat::Rx),
+            _ => anyhow::bail!("invalid output format"),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Bin => "bin",
+            OutputFormat::Ihex => "hex",
+            OutputFormat::Srec => "srec",
+            OutputFormat::Memh => "mem",
+            OutputFormat::Logisim => "logisim",
+            OutputFormat::Rx => "rx",
+        }
+    }
+}
+
+pub fn flatten(segments: &[Segment]) -> Vec<u8> {
+    let end = segments
+        .iter()
+        .map(|segment| segment.address as usize + segment.data.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut binary = vec![0; end];
+    for segment in segments {
+        let start = segment.address as usize;
+        binary[start..start + segment.data.len()].copy_from_slice(&segment.data);
+    }
+    binary
+}
+
+pub fn write_image(executable: &Executable, format: &OutputFormat) -> Vec<u8> {
+    let segments = &executable.segments;
+    match format {
+        OutputFormat::Bin => flatten(segments),
+        OutputFormat::Ihex => write_ihex(segments).into_bytes(),
+        OutputFormat::Srec => write_srec(segments).into_bytes(),
+        OutputFormat::Memh => write_memh(segments).into_bytes(),
+        OutputFormat::Logisim => write_logisim(segments).into_bytes(),
+        OutputFormat::Rx => executable.to_bytes(),
+    }
+}
+
+pub fn read_image(data: &[u8], format: &OutputFormat) -> Result<Executable> {
+    let segments = match format {
+        OutputFormat::Bin => vec![Segment {
+            address: 0,
+            data: data.to_vec(),
+        }],
+        OutputFormat::Rx => return Executable::from_bytes(data),
+        _ => {
+            let text = std::str::from_utf8(data).context("image is not valid text")?;
+            match format {
+                OutputFormat::Ihex => read_ihex(text)?,
+                OutputFormat::Srec => read_srec(text)?,
+                OutputFormat::Memh => read_memh(text)?,
+                OutputFormat::Logisim => read_logisim(text)?,
+                OutputFormat::Bin | OutputFormat::Rx => unreachable!(),
+            }
+        }
+    };
+    Ok(Executable::from_segments(segments))
+}
+
+fn push_bytes(segments: &mut Vec<Segment>, address: u16, bytes: &[u8]) {
+    if let Some(last) = segments.last_mut()
+        && last.address as usize + last.data.len() == address as usize
+    {
+        last.data.extend_from_slice(bytes);
+        return;
+    }
+    segments.push(Segment {
+        address,
+        data: bytes.to_vec(),
+    });
+}
+
+fn parse_hex_bytes(text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        bail!("odd number of hex digits");
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| Ok(u8::from_str_radix(&text[index..index + 2], 16)?))
+        .collect()
+}
+
+fn write_ihex(segments: &[Segment]) -> String {
+    let mut result = String::new();
+
+    for segment in segments {
+        for (index, chunk) in segment.data.chunks(IHEX_BYTES_PER_RECORD).enumerate() {
+            let address = segment
+                .address
+                .wrapping_add((index * IHEX_BYTES_PER_RECORD) as u16);
+            let mut record = vec![chunk.len() as u8, (address >> 8) as u8, address as u8, 0x00];
+            record.extend_from_slice(chunk);
+            let checksum = record
+                .iter()
+                .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+                .wrapping_neg();
+
+            result.push(':');
+            for byte in record {
+                let _ = write!(result, "{byte:02X}");
+            }
+            let _ = writeln!(result, "{checksum:02X}");
+        }
+    }
+
+    result.push_str(":00000001FF\n");
+    result
+}
+
+fn read_ihex(text: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(record) = line.strip_prefix(':') else {
+            bail!("line {}: missing ':' record marker", index + 1);
+        };
+        let record = parse_hex_bytes(record).with_context(|| format!("line {}", index + 1))?;
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            bail!("line {}: invalid record length", index + 1);
+        }
+        if record.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            bail!("line {}: invalid checksum", index + 1);
+        }
+
+        let address = u16::from_be_bytes([record[1], record[2]]);
+        let data = &record[4..record.len() - 1];
+        match record[3] {
+            0x00 => push_bytes(&mut segments, address, data),
+            0x01 => break,
+            0x02 | 0x04 if data.iter().all(|byte| *byte == 0) => {}
+            0x02 => bail!(
+                "line {}: segment address does not fit in 16 bits",
+                index + 1
+            ),
+            0x04 => bail!(
+                "line {}: extended address does not fit in 16 bits",
+                index + 1
+            ),
+            0x03 | 0x05 => {}
+            kind => bail!("line {}: unsupported record type {kind:02X}", index + 1),
+        }
+    }
+
+    Ok(segments)
+}
+
+fn srec_line(kind: char, address: u16, data: &[u8]) -> String {
+    let mut record = vec![(data.len() + 3) as u8, (address >> 8) as u8, address as u8];
+    record.extend_from_slice(data);
+    let checksum = !record.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+
+    let mut line = format!("S{kind}");
+    for byte in record {
+        let _ = write!(line, "{byte:02X}");
+    }
+    let _ = writeln!(line, "{checksum:02X}");
+    line
+}
+
+fn write_srec(segments: &[Segment]) -> String {
+    let mut result = srec_line('0', 0, b"risc16");
+    let mut count = 0u16;
+
+    for segment in segments {
+        for (index, chunk) in segment.data.chunks(SREC_BYTES_PER_RECORD).enumerate() {
+            let address = segment
+                .address
+                .wrapping_add((index * SREC_BYTES_PER_RECORD) as u16);
+            result.push_str(&srec_line('1', address, chunk));
+            count = count.wrapping_add(1);
+        }
+    }
+
+    result.push_str(&srec_line('5', count, &[]));
+    result.push_str(&srec_line('9', 0, &[]));
+    result
+}
+
+fn read_srec(text: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (Some('S'), Some(kind)) = (line.chars().next(), line.chars().nth(1)) else {
+            bail!("line {}: missing 'S' record marker", index + 1);
+        };
+        let record = parse_hex_bytes(&line[2..]).with_context(|| format!("line {}", index + 1))?;
+        if record.len() < 3 || record.len() != record[0] as usize + 1 {
+            bail!("line {}: invalid record length", index + 1);
+        }
+        if record.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0xff {
+            bail!("line {}: invalid checksum", index + 1);
+        }
+
+        match kind {
+            '1' => {
+                let address = u16::from_be_bytes([record[1], record[2]]);
+                push_bytes(&mut segments, address, &record[3..record.len() - 1]);
+            }
+            '0' | '5' | '9' => {}
+            '2' | '3' => bail!("line {}: address does not fit in 16 bits", index + 1),
+            kind => bail!("line {}: unsupported record type S{kind}", index + 1),
+        }
+    }
+
+    Ok(segments)
+}
+
+fn words(data: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    data.chunks(2).map(|chunk| match *chunk {
+        [low, high] => u16::from_le_bytes([low, high]),
+        [low] => low as u16,
+        _ => unreachable!(),
+    })
+}
+
+fn write_memh(segments: &[Segment]) -> String {
+    let mut result = String::new();
+
+    for segment in segments {
+        let (address, data) = if segment.address.is_multiple_of(2) {
+            (segment.address, segment.data.clone())
+        } else {
+            let mut data = vec![0];
+            data.extend_from_slice(&segment.data);
+            (segment.address - 1, data)
+        };
+
+        let _ = writeln!(result, "@{:04x}", address >> 1);
+        let words: Vec<u16> = words(&data).collect();
+        for line in words.chunks(MEMH_WORDS_PER_LINE) {
+            let line: Vec<String> = line.iter().map(|word| format!("{word:04x}")).collect();
+            let _ = writeln!(result, "{}", line.join(" "));
+        }
+    }
+
+    result
+}
+
+fn read_memh(text: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut address = 0u16;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or("");
+        for token in line.split_whitespace() {
+            if let Some(word_address) = token.strip_prefix('@') {
+                let word_address = u16::from_str_radix(word_address, 16)
+                    .with_context(|| format!("line {}: invalid address", index + 1))?;
+                if word_address > 0x7fff {
+                    bail!("line {}: address does not fit in 16 bits", index + 1);
+                }
+                address = word_address << 1;
+            } else {
+                let word = u16::from_str_radix(token, 16)
+                    .with_context(|| format!("line {}: invalid word", index + 1))?;
+                push_bytes(&mut segments, address, &word.to_le_bytes());
+                address = address.wrapping_add(2);
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn write_logisim(segments: &[Segment]) -> String {
+    let mut result = String::from("v2.0 raw\n");
+    let words: Vec<u16> = words(&flatten(segments)).collect();
+
+    let mut entries = Vec::new();
+    let mut index = 0;
+    while index < words.len() {
+        let run = words[index..]
+            .iter()
+            .take_while(|word| **word == words[index])
+            .count();
+        if run > 1 {
+            entries.push(format!("{run}*{:x}", words[index]));
+        } else {
+            entries.push(format!("{:x}", words[index]));
+        }
+        index += run;
+    }
+
+    for line in entries.chunks(LOGISIM_WORDS_PER_LINE) {
+        let _ = writeln!(result, "{}", line.join(" "));
+    }
+    result
+}
+
+fn read_logisim(text: &str) -> Result<Vec<Segment>> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim) != Some("v2.0 raw") {
+        bail!("missing 'v2.0 raw' header");
+    }
+
+    let mut data = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        for token in line.split_whitespace() {
+            let (count, word) = match token.split_once('*') {
+                Some((count, word)) => (
+                    count
+                        .parse::<usize>()
+                        .with_context(|| format!("line {}: invalid repeat count", index + 2))?,
+                    word,
+                ),
+                None => (1, token),
+            };
+            let word = u16::from_str_radix(word, 16)
+                .with_context(|| format!("line {}: invalid word", index + 2))?;
+            if count > (0x10000 - data.len()) / 2 {
+                bail!("line {}: image does not fit in memory", index + 2);
+            }
+            for _ in 0..count {
+                data.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+    }
+
+    Ok(vec![Segment { address: 0, data }])
+}
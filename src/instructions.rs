@@ -1,40 +1,357 @@
-pub enum Instruction {
-    Add { rd: usize, ra: usize, rb: usize },
-    AddWithCarry { rd: usize, ra: usize, rb: usize },
-    Subtract { rd: usize, ra: usize, rb: usize },
-    SubtractWithBorrow { rd: usize, ra: usize, rb: usize },
-    And { rd: usize, ra: usize, rb: usize },
-    Or { rd: usize, ra: usize, rb: usize },
-    Xor { rd: usize, ra: usize, rb: usize },
-    Not { rd: usize, ra: usize },
-    LogicalLeftShift { rd: usize, rs: usize, imm: u16 },
-    LogicalRightShift { rd: usize, rs: usize, imm: u16 },
-    ArithmeticRightShift { rd: usize, rs: usize, imm: u16 },
-    RotateRight { rd: usize, rs: usize, imm: u16 },
-    AddImmediate { rd: usize, imm: u16 },
-    SubImmediate { rd: usize, imm: u16 },
-    Compare { ra: usize, rb: usize },
-    CompareLowBytes { ra: usize, rb: usize },
-    CompareHighBytes { ra: usize, rb: usize },
-    CompareImmediateWithLowByte { rs: usize, imm: u16 },
-    CompareImmediateWithHighByte { rs: usize, imm: u16 },
-    MoveImmediateToLowByte { rd: usize, imm: u16 },
-    MoveImmediateToHighByte { rd: usize, imm: u16 },
-    LoadWord { rd: usize, rs: usize },
-    LoadToLowByte { rd: usize, rs: usize },
-    LoadToHighByte { rd: usize, rs: usize },
-    StoreWord { rs: usize, rd: usize },
-    StoreFromLowByte { rs: usize, rd: usize },
-    StoreFromHighByte { rs: usize, rd: usize },
-    JumpToOffset { offset: i16 },
-    JumpToPointer { rs: usize },
-    BranchIfCarry { offset: i16 },
-    BranchIfNotCarry { offset: i16 },
-    BranchIfOverflow { offset: i16 },
-    BranchIfNotOverflow { offset: i16 },
-    BranchIfZero { offset: i16 },
-    BranchIfNotZero { offset: i16 },
-    BranchIfSigned { offset: i16 },
-    BranchIfNotSigned { offset: i16 },
-    Halt,
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operand {
+    Register,
+    Shift,
+    Immediate,
+    Byte,
+    Offset9,
+    Offset12,
+}
+
+pub struct Field {
+    pub name: &'static str,
+    pub operand: Operand,
+    pub shift: u32,
+}
+
+pub struct InstructionDef {
+    pub name: &'static str,
+    pub mnemonic: &'static str,
+    pub syntax: &'static str,
+    pub pattern: u16,
+    pub mask: u16,
+    pub fields: &'static [Field],
+    pub flags: &'static str,
+    pub description: &'static str,
+}
+
+impl Operand {
+    pub fn width(self) -> u32 {
+        match self {
+            Operand::Register => 3,
+            Operand::Shift => 4,
+            Operand::Immediate | Operand::Byte => 8,
+            Operand::Offset9 => 9,
+            Operand::Offset12 => 12,
+        }
+    }
+
+    fn ruledef_type(self) -> &'static str {
+        match self {
+            Operand::Register => "register",
+            Operand::Shift => "u4",
+            Operand::Immediate => "u8",
+            Operand::Byte => "i8",
+            Operand::Offset9 => "off9",
+            Operand::Offset12 => "off12",
+        }
+    }
+
+    fn extract(self, word: u16, shift: u32) -> i32 {
+        let width = self.width();
+        let value = ((word >> shift) & ((1 << width) - 1)) as i32;
+        match self {
+            Operand::Offset9 | Operand::Offset12 => (value << (32 - width)) >> (32 - width),
+            _ => value,
+        }
+    }
+
+    fn insert(self, value: i32, shift: u32) -> u16 {
+        ((value as u16) & ((1 << self.width()) - 1)) << shift
+    }
+
+    fn format(self, value: i32, address: u16) -> String {
+        match self {
+            Operand::Register => format!("r{value}"),
+            Operand::Shift | Operand::Immediate => value.to_string(),
+            Operand::Byte => format!("0x{value:02x}"),
+            Operand::Offset9 | Operand::Offset12 => {
+                let pc = address.wrapping_add(2);
+                format!("0x{:04x}", pc.wrapping_add_signed((value as i16) << 1))
+            }
+        }
+    }
+}
+
+macro_rules! operand_type {
+    (Register) => {
+        usize
+    };
+    (Shift) => {
+        u16
+    };
+    (Immediate) => {
+        u16
+    };
+    (Byte) => {
+        u16
+    };
+    (Offset9) => {
+        i16
+    };
+    (Offset12) => {
+        i16
+    };
+}
+
+macro_rules! instruction_set {
+    ($(
+        $name:ident $({ $($field:ident: $operand:ident @ $shift:literal),* })?
+            => $mnemonic:literal $syntax:literal, $pattern:literal / $mask:literal,
+            $flags:literal, $description:literal;
+    )*) => {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum Instruction {
+            $($name $({ $($field: operand_type!($operand)),* })?,)*
+        }
+
+        pub const INSTRUCTIONS: &[InstructionDef] = &[$(
+            InstructionDef {
+                name: stringify!($name),
+                mnemonic: $mnemonic,
+                syntax: $syntax,
+                pattern: $pattern,
+                mask: $mask,
+                fields: &[$($(Field {
+                    name: stringify!($field),
+                    operand: Operand::$operand,
+                    shift: $shift,
+                }),*)?],
+                flags: $flags,
+                description: $description,
+            },
+        )*];
+
+        impl Instruction {
+            pub fn decode(word: u16) -> Option<Instruction> {
+                $(if word & $mask == $pattern {
+                    return Some(Instruction::$name $({ $(
+                        $field: Operand::$operand.extract(word, $shift) as operand_type!($operand),
+                    )* })?);
+                })*
+                None
+            }
+
+            pub fn encode(&self) -> u16 {
+                match *self {
+                    $(Instruction::$name $({ $($field),* })? => {
+                        $pattern $($(| Operand::$operand.insert($field as i32, $shift))*)?
+                    })*
+                }
+            }
+        }
+    };
+}
+
+instruction_set! {
+    Add { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "ADD" "{rd}, {ra}, {rb}", 0x0000 / 0xf007, "C O Z S", "rd = ra + rb";
+    AddWithCarry { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "ADC" "{rd}, {ra}, {rb}", 0x0001 / 0xf007, "C O Z S", "rd = ra + rb + C";
+    Subtract { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "SUB" "{rd}, {ra}, {rb}", 0x0002 / 0xf007, "C O Z S", "rd = ra - rb";
+    SubtractWithBorrow { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "SBB" "{rd}, {ra}, {rb}", 0x0003 / 0xf007, "C O Z S", "rd = ra - rb - C";
+    And { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "AND" "{rd}, {ra}, {rb}", 0x0004 / 0xf007, "C O Z S", "rd = ra & rb";
+    Or { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "OR" "{rd}, {ra}, {rb}", 0x0005 / 0xf007, "C O Z S", "rd = ra | rb";
+    Xor { rd: Register @ 9, ra: Register @ 6, rb: Register @ 3 }
+        => "XOR" "{rd}, {ra}, {rb}", 0x0006 / 0xf007, "C O Z S", "rd = ra ^ rb";
+    Not { rd: Register @ 9, ra: Register @ 6 }
+        => "NOT" "{rd}, {ra}", 0x0007 / 0xf007, "C O Z S", "rd = !ra";
+    LogicalLeftShift { rd: Register @ 9, rs: Register @ 6, imm: Shift @ 2 }
+        => "SHL" "{rd}, {rs}, {imm}", 0x1000 / 0xf003, "C O Z S", "rd = rs << imm";
+    LogicalRightShift { rd: Register @ 9, rs: Register @ 6, imm: Shift @ 2 }
+        => "SHR" "{rd}, {rs}, {imm}", 0x1001 / 0xf003, "C O Z S", "rd = rs >> imm";
+    ArithmeticRightShift { rd: Register @ 9, rs: Register @ 6, imm: Shift @ 2 }
+        => "SAR" "{rd}, {rs}, {imm}", 0x1002 / 0xf003, "C O Z S", "rd = rs >> imm, keeping the sign";
+    RotateRight { rd: Register @ 9, rs: Register @ 6, imm: Shift @ 2 }
+        => "ROR" "{rd}, {rs}, {imm}", 0x1003 / 0xf003, "C O Z S", "rd = rs rotated right by imm";
+    AddImmediate { rd: Register @ 9, imm: Immediate @ 1 }
+        => "ADDI" "{rd}, {imm}", 0x2000 / 0xf001, "C O Z S", "rd = rd + imm";
+    SubImmediate { rd: Register @ 9, imm: Immediate @ 1 }
+        => "SUBI" "{rd}, {imm}", 0x2001 / 0xf001, "C O Z S", "rd = rd - imm";
+    Compare { ra: Register @ 9, rb: Register @ 6 }
+        => "CMP" "{ra}, {rb}", 0x3000 / 0xf003, "C O Z S", "flags of ra - rb";
+    CompareLowBytes { ra: Register @ 9, rb: Register @ 6 }
+        => "CMPL" "{ra}, {rb}", 0x3002 / 0xf003, "C O Z S", "flags of the low bytes of ra - rb";
+    CompareHighBytes { ra: Register @ 9, rb: Register @ 6 }
+        => "CMPH" "{ra}, {rb}", 0x3003 / 0xf003, "C O Z S", "flags of the high bytes of ra - rb";
+    CompareImmediateWithLowByte { rs: Register @ 9, imm: Byte @ 1 }
+        => "CMPL" "{rs}, {imm}", 0x4000 / 0xf001, "C O Z S", "flags of the low byte of rs - imm";
+    CompareImmediateWithHighByte { rs: Register @ 9, imm: Byte @ 1 }
+        => "CMPH" "{rs}, {imm}", 0x4001 / 0xf001, "C O Z S", "flags of the high byte of rs - imm";
+    MoveImmediateToLowByte { rd: Register @ 9, imm: Byte @ 1 }
+        => "MOVL" "{rd}, {imm}", 0x5000 / 0xf001, "-", "low byte of rd = imm";
+    MoveImmediateToHighByte { rd: Register @ 9, imm: Byte @ 1 }
+        => "MOVH" "{rd}, {imm}", 0x5001 / 0xf001, "-", "high byte of rd = imm";
+    LoadWord { rd: Register @ 9, rs: Register @ 6 }
+        => "LDW" "{rd}, [{rs}]", 0x6000 / 0xf003, "-", "rd = word at rs";
+    LoadToLowByte { rd: Register @ 9, rs: Register @ 6 }
+        => "LDL" "{rd}, [{rs}]", 0x6002 / 0xf003, "-", "low byte of rd = byte at rs";
+    LoadToHighByte { rd: Register @ 9, rs: Register @ 6 }
+        => "LDH" "{rd}, [{rs}]", 0x6003 / 0xf003, "-", "high byte of rd = byte at rs";
+    StoreWord { rs: Register @ 9, rd: Register @ 6 }
+        => "STW" "{rs}, [{rd}]", 0x7000 / 0xf003, "-", "word at rd = rs";
+    StoreFromLowByte { rs: Register @ 9, rd: Register @ 6 }
+        => "STL" "{rs}, [{rd}]", 0x7002 / 0xf003, "-", "byte at rd = low byte of rs";
+    StoreFromHighByte { rs: Register @ 9, rd: Register @ 6 }
+        => "STH" "{rs}, [{rd}]", 0x7003 / 0xf003, "-", "byte at rd = high byte of rs";
+    JumpToOffset { offset: Offset12 @ 0 }
+        => "JMP" "{offset}", 0x8000 / 0xf000, "-", "jump to a label";
+    JumpToPointer { rs: Register @ 9 }
+        => "JMP" "[{rs}]", 0x9000 / 0xf000, "-", "jump to the address in rs";
+    BranchIfCarry { offset: Offset9 @ 3 }
+        => "BC" "{offset}", 0xa000 / 0xf007, "-", "branch if C";
+    BranchIfNotCarry { offset: Offset9 @ 3 }
+        => "BNC" "{offset}", 0xa001 / 0xf007, "-", "branch if not C";
+    BranchIfOverflow { offset: Offset9 @ 3 }
+        => "BO" "{offset}", 0xa002 / 0xf007, "-", "branch if O";
+    BranchIfNotOverflow { offset: Offset9 @ 3 }
+        => "BNO" "{offset}", 0xa003 / 0xf007, "-", "branch if not O";
+    BranchIfZero { offset: Offset9 @ 3 }
+        => "BZ" "{offset}", 0xa004 / 0xf007, "-", "branch if Z";
+    BranchIfNotZero { offset: Offset9 @ 3 }
+        => "BNZ" "{offset}", 0xa005 / 0xf007, "-", "branch if not Z";
+    BranchIfSigned { offset: Offset9 @ 3 }
+        => "BS" "{offset}", 0xa006 / 0xf007, "-", "branch if S";
+    BranchIfNotSigned { offset: Offset9 @ 3 }
+        => "BNS" "{offset}", 0xa007 / 0xf007, "-", "branch if not S";
+    Halt
+        => "HLT" "", 0xf000 / 0xf000, "-", "stop the processor";
+}
+
+impl InstructionDef {
+    fn field_at(&self, bit: u32) -> Option<&Field> {
+        self.fields
+            .iter()
+            .find(|field| field.shift + field.operand.width() - 1 == bit)
+    }
+
+    fn encoding(&self) -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut fixed = String::new();
+        let mut bit = 16;
+        while bit > 0 {
+            if bit == 12 && !fixed.is_empty() {
+                parts.push(std::mem::take(&mut fixed));
+            }
+            if let Some(field) = self.field_at(bit - 1) {
+                if !fixed.is_empty() {
+                    parts.push(std::mem::take(&mut fixed));
+                }
+                parts.push(field.name.to_string());
+                bit -= field.operand.width();
+            } else {
+                fixed.push(if self.pattern >> (bit - 1) & 1 != 0 {
+                    '1'
+                } else {
+                    '0'
+                });
+                bit -= 1;
+            }
+        }
+        if !fixed.is_empty() {
+            parts.push(fixed);
+        }
+        parts
+    }
+
+    pub fn ruledef(&self) -> String {
+        let mut syntax = self.syntax.to_string();
+        for field in self.fields {
+            syntax = syntax.replace(
+                &format!("{{{}}}", field.name),
+                &format!("{{{}: {}}}", field.name, field.operand.ruledef_type()),
+            );
+        }
+        let encoding = self
+            .encoding()
+            .into_iter()
+            .map(|part| {
+                if !part.starts_with(['0', '1']) {
+                    part
+                } else if part.len() % 4 == 0 {
+                    let value = u16::from_str_radix(&part, 2).unwrap_or(0);
+                    format!("0x{value:0width$x}", width = part.len() / 4)
+                } else {
+                    format!("0b{part}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" @ ");
+        format!("{} {syntax}", self.mnemonic).trim_end().to_string()
+            + &format!(" => le({encoding})")
+    }
+}
+
+impl Instruction {
+    pub fn definition(&self) -> &'static InstructionDef {
+        let word = self.encode();
+        INSTRUCTIONS
+            .iter()
+            .find(|definition| word & definition.mask == definition.pattern)
+            .expect("every instruction has a definition")
+    }
+
+    pub fn class(&self) -> &'static str {
+        match self.encode() >> 12 {
+            0x0 => "alu",
+            0x1 => "shift",
+            0x2 => "immediate",
+            0x3 | 0x4 => "compare",
+            0x5 => "move",
+            0x6 => "load",
+            0x7 => "store",
+            0x8 | 0x9 => "jump",
+            0xa => "branch",
+            _ => "halt",
+        }
+    }
+
+    pub fn to_assembly(&self, address: u16) -> String {
+        let definition = self.definition();
+        let word = self.encode();
+        let mut operands = definition.syntax.to_string();
+        for field in definition.fields {
+            let value = field.operand.extract(word, field.shift);
+            operands = operands.replace(
+                &format!("{{{}}}", field.name),
+                &field.operand.format(value, address),
+            );
+        }
+        format!("{} {operands}", definition.mnemonic)
+            .trim_end()
+            .to_string()
+    }
+}
+
+pub fn ruledef() -> String {
+    let mut result = String::from("#once\n\n#include \"<std>/types.asm\"\n\n#ruledef {\n");
+    for definition in INSTRUCTIONS {
+        let _ = writeln!(result, "    {}", definition.ruledef());
+    }
+    result.push_str("}\n");
+    result
+}
+
+pub fn reference() -> String {
+    let mut result = String::from("| Instruction | Encoding | Flags | Operation |\n");
+    result.push_str("| --- | --- | --- | --- |\n");
+    for definition in INSTRUCTIONS {
+        let syntax =
+            format!("{} {}", definition.mnemonic, definition.syntax).replace(['{', '}'], "");
+        let _ = writeln!(
+            result,
+            "| `{}` | `{}` | {} | {} |",
+            syntax.trim_end(),
+            definition.encoding().join(" "),
+            definition.flags,
+            definition.description.replace('|', "\\|")
+        );
+    }
+    result
 }
@@ -0,0 +1,161 @@
+use crate::utils::{parse_u16, parse_u32};
+use anyhow::{Context, Result, bail};
+use std::fmt::Write;
+use std::fs;
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub struct Bank {
+    pub name: String,
+    pub address: u16,
+    pub size: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Layout {
+    pub banks: Vec<Bank>,
+}
+
+impl FromStr for Bank {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let Some((name, range)) = s.split_once('=') else {
+            anyhow::bail!("expected a bank like code=0x0000:0x4000");
+        };
+        let Some((address, size)) = range.split_once(':') else {
+            anyhow::bail!("expected a bank like code=0x0000:0x4000");
+        };
+        Ok(Bank {
+            name: name.trim().to_string(),
+            address: parse_u16(address.trim()).context("invalid bank address")?,
+            size: parse_u32(size.trim()).context("invalid bank size")?,
+        })
+    }
+}
+
+impl Bank {
+    fn new(name: &str, address: u16, size: u32) -> Bank {
+        Bank {
+            name: name.to_string(),
+            address,
+            size,
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        self.address as usize + self.size as usize
+    }
+
+    pub fn contains(&self, address: u16) -> bool {
+        (self.address as usize..self.end()).contains(&(address as usize))
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !matches!(self.name.as_str(), "bss" | "stack")
+    }
+
+    pub fn is_writable(&self) -> bool {
+        !matches!(self.name.as_str(), "code" | "rodata")
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.name == "code"
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            banks: vec![
+                Bank::new("code", 0x0000, 0x4000),
+                Bank::new("rodata", 0x4000, 0x1000),
+                Bank::new("data", 0x5000, 0x3000),
+                Bank::new("bss", 0x8000, 0x6000),
+                Bank::new("stack", 0xe000, 0x2000),
+            ],
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut banks = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split(['#', ';']).next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            banks.push(
+                line.parse()
+                    .with_context(|| format!("line {}", index + 1))?,
+            );
+        }
+        Ok(Layout { banks })
+    }
+}
+
+impl Layout {
+    pub fn new(banks: Vec<Bank>) -> Result<Layout> {
+        let layout = Layout { banks };
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    pub fn load(path: Option<&str>, banks: &[Bank]) -> Result<Layout> {
+        let mut layout = match path {
+            Some(path) => fs::read_to_string(path)
+                .with_context(|| format!("unable to read {path}"))?
+                .parse()
+                .with_context(|| format!("invalid layout {path}"))?,
+            None => Layout::default(),
+        };
+        for bank in banks {
+            match layout
+                .banks
+                .iter_mut()
+                .find(|existing| existing.name == bank.name)
+            {
+                Some(existing) => *existing = bank.clone(),
+                None => layout.banks.push(bank.clone()),
+            }
+        }
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (index, bank) in self.banks.iter().enumerate() {
+            if bank.size == 0 {
+                bail!("bank {} is empty", bank.name);
+            }
+            if bank.end() > 0x10000 {
+                bail!("bank {} does not fit in memory", bank.name);
+            }
+            if let Some(other) = self.banks[..index].iter().find(|other| {
+                (bank.address as usize) < other.end() && (other.address as usize) < bank.end()
+            }) {
+                bail!("bank {} overlaps bank {}", bank.name, other.name);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn bank(&self, name: &str) -> Option<&Bank> {
+        self.banks.iter().find(|bank| bank.name == name)
+    }
+
+    pub fn bankdefs(&self) -> String {
+        let mut result = String::from("#once\n");
+        for bank in &self.banks {
+            let _ = writeln!(result, "\n#bankdef {}\n{{", bank.name);
+            let _ = writeln!(result, "    #addr 0x{:04x}", bank.address);
+            let _ = writeln!(result, "    #size 0x{:04x}", bank.size);
+            if bank.is_loaded() {
+                let _ = writeln!(result, "    #outp 8 * 0x{:04x}", bank.address);
+            }
+            let _ = writeln!(result, "}}");
+        }
+        result
+    }
+}
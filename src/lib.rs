@@ -1,3 +1,17 @@
 pub mod assembler;
+pub mod coverage;
+pub mod debugger;
 pub mod emulator;
+pub mod executable;
+pub mod formats;
 pub mod instructions;
+pub mod layout;
+pub mod linker;
+pub mod object;
+pub mod pipeline;
+pub mod profile;
+pub mod project;
+pub mod snapshot;
+pub mod testing;
+pub mod timing;
+pub mod utils;
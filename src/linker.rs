@@ -0,0 +1,204 @@
+use crate::executable::{Executable, IsaProfile, Symbol};
+use crate::formats::{OutputFormat, Segment, write_image};
+use crate::layout::Layout;
+use crate::object::{Object, RelocationKind, RelocationTarget};
+use crate::utils::parse_u16;
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+const ENTRY_SYMBOL: &str = "_start";
+
+#[derive(Clone, Debug)]
+pub struct Placement {
+    pub section: String,
+    pub address: u16,
+}
+
+impl FromStr for Placement {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let Some((section, address)) = s.split_once('=') else {
+            anyhow::bail!("expected a section placement like code=0x0000");
+        };
+        Ok(Placement {
+            section: section.to_string(),
+            address: parse_u16(address).context("invalid section address")?,
+        })
+    }
+}
+
+pub fn default_placements(layout: &Layout) -> Vec<Placement> {
+    layout
+        .banks
+        .iter()
+        .filter(|bank| bank.is_loaded())
+        .map(|bank| Placement {
+            section: bank.name.clone(),
+            address: bank.address,
+        })
+        .collect()
+}
+
+pub fn link(objects: &[Object], layout: &[Placement]) -> Result<Executable> {
+    let mut addresses: Vec<Vec<u16>> = objects
+        .iter()
+        .map(|object| vec![0; object.sections.len()])
+        .collect();
+
+    let mut segments = Vec::new();
+    let mut ranges: Vec<(usize, usize, &str)> = Vec::new();
+
+    for placement in layout {
+        let address = placement.address as usize;
+        let mut data = Vec::new();
+
+        for (object, addresses) in objects.iter().zip(addresses.iter_mut()) {
+            let Some(index) = object.section(&placement.section) else {
+                continue;
+            };
+            if data.len() % 2 != 0 {
+                data.push(0);
+            }
+            addresses[index as usize] = (address + data.len()) as u16;
+            data.extend_from_slice(&object.sections[index as usize].data);
+        }
+
+        let end = address + data.len();
+        if end > 0x10000 {
+            bail!("section {} does not fit in memory", placement.section);
+        }
+        if let Some((_, _, other)) = ranges
+            .iter()
+            .find(|(start, other_end, _)| address < *other_end && *start < end)
+        {
+            bail!("section {} overlaps section {other}", placement.section);
+        }
+        ranges.push((address, end, &placement.section));
+
+        if !data.is_empty() {
+            segments.push(Segment {
+                address: address as u16,
+                data,
+            });
+        }
+    }
+
+    for object in objects {
+        for section in &object.sections {
+            if !section.data.is_empty()
+                && !layout
+                    .iter()
+                    .any(|placement| placement.section == section.name)
+            {
+                bail!("no address given for section {}", section.name);
+            }
+        }
+    }
+
+    let mut symbols: HashMap<&str, u16> = HashMap::new();
+    for (object, addresses) in objects.iter().zip(&addresses) {
+        for export in &object.exports {
+            let address = addresses[export.section as usize].wrapping_add(export.offset);
+            if symbols.insert(&export.name, address).is_some() {
+                bail!("symbol {} is defined more than once", export.name);
+            }
+        }
+    }
+
+    for (object, addresses) in objects.iter().zip(&addresses) {
+        for relocation in &object.relocations {
+            let target = match relocation.target {
+                RelocationTarget::Section(section) => addresses[section as usize],
+                RelocationTarget::Import(import) => {
+                    let name = &object.imports[import as usize];
+                    *symbols
+                        .get(name.as_str())
+                        .with_context(|| format!("undefined symbol {name}"))?
+                }
+            };
+            let value = (target as i32 + relocation.addend) as u16;
+            let address = addresses[relocation.section as usize].wrapping_add(relocation.offset);
+
+            let segment = segments
+                .iter_mut()
+                .find(|segment| {
+                    segment.address <= address
+                        && address as usize + 2 <= segment.address as usize + segment.data.len()
+                })
+                .context("relocation outside of section")?;
+            let index = (address - segment.address) as usize;
+            let word = u16::from_le_bytes([segment.data[index], segment.data[index + 1]]);
+
+            let word = match relocation.kind {
+                RelocationKind::Off9 => {
+                    let offset = (value as i32 - address as i32 - 2) >> 1;
+                    if !(-0x100..=0xff).contains(&offset) {
+                        bail!("branch at 0x{address:04x} is out of range of 0x{value:04x}");
+                    }
+                    (word & !(0x1ff << 3)) | (((offset as u16) & 0x1ff) << 3)
+                }
+                RelocationKind::Off12 => {
+                    let offset = (value as i32 - address as i32 - 2) >> 1;
+                    if !(-0x800..=0x7ff).contains(&offset) {
+                        bail!("jump at 0x{address:04x} is out of range of 0x{value:04x}");
+                    }
+                    (word & !0xfff) | ((offset as u16) & 0xfff)
+                }
+                RelocationKind::Low => (word & !(0xff << 1)) | ((value & 0xff) << 1),
+                RelocationKind::High => (word & !(0xff << 1)) | ((value >> 8) << 1),
+                RelocationKind::Word => value,
+            };
+            segment.data[index..index + 2].copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    let mut symbols: Vec<Symbol> = symbols
+        .into_iter()
+        .map(|(name, address)| Symbol {
+            name: name.to_string(),
+            address,
+        })
+        .collect();
+    symbols.sort_by_key(|symbol| symbol.address);
+
+    let mut executable = Executable {
+        profile: IsaProfile::Base,
+        segments,
+        symbols,
+        ..Executable::default()
+    };
+    executable.entry = executable.symbol(ENTRY_SYMBOL).unwrap_or(0);
+    Ok(executable)
+}
+
+pub fn link_files(
+    inputs: Vec<String>,
+    output: String,
+    format: OutputFormat,
+    placements: Vec<Placement>,
+    layout: &Layout,
+) -> Result<()> {
+    let mut sections = default_placements(layout);
+    for placement in placements {
+        match sections
+            .iter_mut()
+            .find(|existing| existing.section == placement.section)
+        {
+            Some(existing) => existing.address = placement.address,
+            None => sections.push(placement),
+        }
+    }
+
+    let objects = inputs
+        .iter()
+        .map(|input| {
+            Object::from_bytes(&fs::read(input)?).with_context(|| format!("unable to read {input}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let executable = link(&objects, &sections)?;
+    fs::write(output, write_image(&executable, &format))?;
+    Ok(())
+}
@@ -1,11 +1,20 @@
-mod utils;
-
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use std::fs;
 
-use crate::utils::parse_u16;
-use risc16::assembler::assemble_to_file;
-use risc16::emulator::{EmulatorOptions, InputFormat, MemoryFormat, emulate_file};
+use risc16::assembler::{AssemblerOptions, Define, assemble_to_file};
+use risc16::emulator::{
+    Dump, EmulatorOptions, Engine, InputFormat, Load, MemoryFormat, ResultFormat, emulate_file,
+};
+use risc16::executable::{Executable, describe};
+use risc16::formats::OutputFormat;
+use risc16::instructions::{reference, ruledef};
+use risc16::layout::{Bank, Layout};
+use risc16::linker::{Placement, link_files};
+use risc16::project::{MANIFEST, build};
+use risc16::testing::{TestOptions, run_tests};
+use risc16::timing::{Latency, Timing};
+use risc16::utils::parse_u16;
 
 #[derive(Parser)]
 #[command(version)]
@@ -19,13 +28,82 @@ enum Commands {
     Assemble {
         input: String,
         output: String,
+        #[arg(short = 'f', long, default_value = "bin", value_parser = clap::value_parser!(OutputFormat))]
+        format: OutputFormat,
+        #[arg(long)]
+        listing: Option<String>,
+        #[arg(short = 'c', long)]
+        object: bool,
+        #[arg(long)]
+        report_relaxations: bool,
+        #[arg(short = 'I', long = "include")]
+        include_dirs: Vec<String>,
+        #[arg(short = 'D', long = "define", value_parser = clap::value_parser!(Define))]
+        defines: Vec<Define>,
+        #[arg(long)]
+        layout: Option<String>,
+        #[arg(short, long = "bank", value_parser = clap::value_parser!(Bank))]
+        banks: Vec<Bank>,
+    },
+    Link {
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        #[arg(short, long)]
+        output: String,
+        #[arg(short = 'f', long, default_value = "rx", value_parser = clap::value_parser!(OutputFormat))]
+        format: OutputFormat,
+        #[arg(short, long = "section", value_parser = clap::value_parser!(Placement))]
+        sections: Vec<Placement>,
+        #[arg(long)]
+        layout: Option<String>,
+        #[arg(short, long = "bank", value_parser = clap::value_parser!(Bank))]
+        banks: Vec<Bank>,
+    },
+    Build {
+        #[arg(long, default_value = MANIFEST)]
+        manifest: String,
+    },
+    Info {
+        input: String,
+    },
+    Isa {
+        #[arg(long)]
+        ruledef: bool,
+    },
+    Test {
+        #[arg(default_value = ".")]
+        paths: Vec<String>,
+        #[arg(short, long, default_value_t = 1_000_000)]
+        cycles: u64,
+        #[arg(long, num_args = 0..=1, default_missing_value = "lcov.info")]
+        coverage: Option<String>,
+        #[arg(long)]
+        timing: Option<String>,
+        #[arg(long = "latency", value_parser = clap::value_parser!(Latency))]
+        latencies: Vec<Latency>,
+        #[arg(short = 'I', long = "include")]
+        include_dirs: Vec<String>,
+        #[arg(short = 'D', long = "define", value_parser = clap::value_parser!(Define))]
+        defines: Vec<Define>,
+        #[arg(long)]
+        layout: Option<String>,
+        #[arg(short, long = "bank", value_parser = clap::value_parser!(Bank))]
+        banks: Vec<Bank>,
     },
     Emulate {
         input: String,
         #[arg(short = 'f', long, default_value = "asm", value_parser = clap::value_parser!(InputFormat))]
         input_format: InputFormat,
+        #[arg(short, long, value_parser = clap::value_parser!(Load))]
+        load: Vec<Load>,
+        #[arg(short, long, value_parser = parse_u16)]
+        entry: Option<u16>,
         #[arg(short, long)]
         step: bool,
+        #[arg(long)]
+        check_abi: bool,
+        #[arg(long)]
+        protect: bool,
         #[arg(short, long)]
         cycles: Option<u64>,
         #[arg(short = 'r', long, default_value_t = false)]
@@ -34,12 +112,40 @@ enum Commands {
         show_flags: bool,
         #[arg(short = 'm', long, default_value_t = false)]
         show_memory: bool,
-        #[arg(long, default_value_t = 0x0100, value_parser = parse_u16)]
-        memory_start: u16,
-        #[arg(long, default_value_t = 0x0110, value_parser = parse_u16)]
-        memory_end: u16,
+        #[arg(long, value_parser = parse_u16)]
+        memory_start: Option<u16>,
+        #[arg(long, value_parser = parse_u16)]
+        memory_end: Option<u16>,
         #[arg(long, default_value = "hex", value_parser = clap::value_parser!(MemoryFormat))]
         memory_format: MemoryFormat,
+        #[arg(short, long = "dump", value_parser = clap::value_parser!(Dump))]
+        dumps: Vec<Dump>,
+        #[arg(short, long, default_value = "text", value_parser = clap::value_parser!(ResultFormat))]
+        output: ResultFormat,
+        #[arg(long)]
+        save_state: Option<String>,
+        #[arg(long, requires = "save_state", conflicts_with = "step")]
+        save_at: Option<u64>,
+        #[arg(long)]
+        load_state: Option<String>,
+        #[arg(long)]
+        timing: Option<String>,
+        #[arg(long = "latency", value_parser = clap::value_parser!(Latency))]
+        latencies: Vec<Latency>,
+        #[arg(long, default_value = "interpreter", value_parser = clap::value_parser!(Engine))]
+        engine: Engine,
+        #[arg(long)]
+        profile: bool,
+        #[arg(long)]
+        folded: Option<String>,
+        #[arg(short = 'I', long = "include")]
+        include_dirs: Vec<String>,
+        #[arg(short = 'D', long = "define", value_parser = clap::value_parser!(Define))]
+        defines: Vec<Define>,
+        #[arg(long)]
+        layout: Option<String>,
+        #[arg(short, long = "bank", value_parser = clap::value_parser!(Bank))]
+        banks: Vec<Bank>,
     },
 }
 
@@ -47,11 +153,83 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Assemble { input, output } => assemble_to_file(input, output),
+        Commands::Assemble {
+            input,
+            output,
+            format,
+            listing,
+            object,
+            report_relaxations,
+            include_dirs,
+            defines,
+            layout,
+            banks,
+        } => {
+            let options = AssemblerOptions {
+                layout: Layout::load(layout.as_deref(), &banks)?,
+                include_dirs,
+                defines,
+                report_relaxations,
+            };
+            assemble_to_file(input, output, format, listing, object, &options)
+        }
+        Commands::Link {
+            inputs,
+            output,
+            format,
+            sections,
+            layout,
+            banks,
+        } => {
+            let layout = Layout::load(layout.as_deref(), &banks)?;
+            link_files(inputs, output, format, sections, &layout)
+        }
+        Commands::Build { manifest } => build(&manifest),
+        Commands::Info { input } => {
+            let executable = Executable::from_bytes(&fs::read(input)?)?;
+            print!("{}", describe(&executable));
+            Ok(())
+        }
+        Commands::Isa { ruledef: true } => {
+            print!("{}", ruledef());
+            Ok(())
+        }
+        Commands::Isa { ruledef: false } => {
+            print!("{}", reference());
+            Ok(())
+        }
+        Commands::Test {
+            paths,
+            cycles,
+            coverage,
+            timing,
+            latencies,
+            include_dirs,
+            defines,
+            layout,
+            banks,
+        } => run_tests(
+            paths,
+            TestOptions {
+                cycles,
+                coverage,
+                timing: Timing::load(timing.as_deref(), &latencies)?,
+                assembler: AssemblerOptions {
+                    layout: Layout::load(layout.as_deref(), &banks)?,
+                    include_dirs,
+                    defines,
+                    ..AssemblerOptions::default()
+                },
+            },
+        ),
         Commands::Emulate {
             input,
             input_format,
+            load,
+            entry,
             step,
+            check_abi,
+            protect,
             cycles,
             show_registers,
             show_flags,
@@ -59,9 +237,24 @@ fn main() -> Result<()> {
             memory_start,
             memory_end,
             memory_format,
+            dumps,
+            output,
+            save_state,
+            save_at,
+            load_state,
+            timing,
+            latencies,
+            engine,
+            profile,
+            folded,
+            include_dirs,
+            defines,
+            layout,
+            banks,
         } => emulate_file(
             input,
             input_format,
+            load,
             EmulatorOptions {
                 step,
                 cycles,
@@ -71,6 +264,24 @@ fn main() -> Result<()> {
                 memory_start,
                 memory_end,
                 memory_format,
+                dumps,
+                entry,
+                check_abi,
+                protect,
+                output,
+                save_state,
+                save_at,
+                load_state,
+                timing: Timing::load(timing.as_deref(), &latencies)?,
+                engine,
+                profile,
+                folded,
+                assembler: AssemblerOptions {
+                    layout: Layout::load(layout.as_deref(), &banks)?,
+                    include_dirs,
+                    defines,
+                    ..AssemblerOptions::default()
+                },
             },
         ),
     }
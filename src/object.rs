@@ -0,0 +1,192 @@
+use crate::utils::{Reader, write_string};
+use anyhow::{Result, bail};
+
+pub const MAGIC: &[u8; 4] = b"RO16";
+pub const VERSION: u8 = 1;
+
+#[derive(Clone, Debug)]
+pub struct Section {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Export {
+    pub name: String,
+    pub section: u16,
+    pub offset: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelocationKind {
+    Off9,
+    Off12,
+    Low,
+    High,
+    Word,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelocationTarget {
+    Section(u16),
+    Import(u16),
+}
+
+#[derive(Clone, Debug)]
+pub struct Relocation {
+    pub section: u16,
+    pub offset: u16,
+    pub kind: RelocationKind,
+    pub target: RelocationTarget,
+    pub addend: i32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Object {
+    pub sections: Vec<Section>,
+    pub exports: Vec<Export>,
+    pub imports: Vec<String>,
+    pub relocations: Vec<Relocation>,
+}
+
+impl RelocationKind {
+    fn id(&self) -> u8 {
+        match self {
+            RelocationKind::Off9 => 0,
+            RelocationKind::Off12 => 1,
+            RelocationKind::Low => 2,
+            RelocationKind::High => 3,
+            RelocationKind::Word => 4,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<RelocationKind> {
+        match id {
+            0 => Ok(RelocationKind::Off9),
+            1 => Ok(RelocationKind::Off12),
+            2 => Ok(RelocationKind::Low),
+            3 => Ok(RelocationKind::High),
+            4 => Ok(RelocationKind::Word),
+            _ => bail!("unknown relocation kind {id}"),
+        }
+    }
+}
+
+impl Object {
+    pub fn section(&self, name: &str) -> Option<u16> {
+        self.sections
+            .iter()
+            .position(|section| section.name == name)
+            .map(|index| index as u16)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.sections.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.exports.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.imports.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.relocations.len() as u32).to_le_bytes());
+
+        for section in &self.sections {
+            write_string(&mut bytes, &section.name);
+            bytes.extend_from_slice(&(section.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&section.data);
+        }
+
+        for export in &self.exports {
+            write_string(&mut bytes, &export.name);
+            bytes.extend_from_slice(&export.section.to_le_bytes());
+            bytes.extend_from_slice(&export.offset.to_le_bytes());
+        }
+
+        for import in &self.imports {
+            write_string(&mut bytes, import);
+        }
+
+        for relocation in &self.relocations {
+            let (target_kind, target) = match relocation.target {
+                RelocationTarget::Section(section) => (0u8, section),
+                RelocationTarget::Import(import) => (1u8, import),
+            };
+            bytes.extend_from_slice(&relocation.section.to_le_bytes());
+            bytes.extend_from_slice(&relocation.offset.to_le_bytes());
+            bytes.push(relocation.kind.id());
+            bytes.push(target_kind);
+            bytes.extend_from_slice(&target.to_le_bytes());
+            bytes.extend_from_slice(&relocation.addend.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Object> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            bail!("not a RISC16 object file");
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            bail!("unsupported object file version {version}");
+        }
+        let section_count = reader.u16()?;
+        let export_count = reader.u16()?;
+        let import_count = reader.u16()?;
+        let relocation_count = reader.u32()?;
+
+        let mut sections = Vec::new();
+        for _ in 0..section_count {
+            let name = reader.string()?;
+            let size = reader.u32()? as usize;
+            let data = reader.take(size)?.to_vec();
+            sections.push(Section { name, data });
+        }
+
+        let mut exports = Vec::new();
+        for _ in 0..export_count {
+            let name = reader.string()?;
+            let section = reader.u16()?;
+            let offset = reader.u16()?;
+            exports.push(Export {
+                name,
+                section,
+                offset,
+            });
+        }
+
+        let mut imports = Vec::new();
+        for _ in 0..import_count {
+            imports.push(reader.string()?);
+        }
+
+        let mut relocations = Vec::new();
+        for _ in 0..relocation_count {
+            let section = reader.u16()?;
+            let offset = reader.u16()?;
+            let kind = RelocationKind::from_id(reader.u8()?)?;
+            let target = match (reader.u8()?, reader.u16()?) {
+                (0, section) => RelocationTarget::Section(section),
+                (1, import) => RelocationTarget::Import(import),
+                (kind, _) => bail!("unknown relocation target kind {kind}"),
+            };
+            let addend = reader.u32()? as i32;
+            relocations.push(Relocation {
+                section,
+                offset,
+                kind,
+                target,
+                addend,
+            });
+        }
+
+        Ok(Object {
+            sections,
+            exports,
+            imports,
+            relocations,
+        })
+    }
+}
@@ -0,0 +1,491 @@
+use crate::emulator::{Cpu, Flags, Memory};
+use crate::instructions::Instruction;
+use anyhow::{Result, bail};
+use serde::Serialize;
+
+#[derive(Clone, Copy)]
+struct Fetched {
+    address: u16,
+    word: u16,
+}
+
+struct Decoded {
+    address: u16,
+    instruction: std::result::Result<Instruction, String>,
+}
+
+#[derive(Clone, Copy)]
+enum Access {
+    None,
+    LoadWord(u16),
+    LoadLowByte(u16),
+    LoadHighByte(u16),
+    StoreWord(u16, u16),
+    StoreByte(u16, u8),
+}
+
+struct Latch {
+    address: u16,
+    next: u16,
+    instruction: Instruction,
+    destination: Option<usize>,
+    value: u16,
+    flags: Option<Flags>,
+    access: Access,
+    fault: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub cycles: u64,
+    pub instructions: u64,
+    pub stalls: u64,
+    pub flushes: u64,
+}
+
+impl Stats {
+    pub fn cpi(&self) -> f64 {
+        self.cycles as f64 / self.instructions.max(1) as f64
+    }
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    fetch_address: u16,
+    stopped: bool,
+    retired_at: u64,
+    fetched: Option<Fetched>,
+    decoded: Option<Decoded>,
+    executed: Option<Latch>,
+    accessed: Option<Latch>,
+    pub stats: Stats,
+}
+
+fn reads(instruction: &Instruction) -> [Option<usize>; 2] {
+    use Instruction::*;
+    match *instruction {
+        Add { ra, rb, .. }
+        | AddWithCarry { ra, rb, .. }
+        | Subtract { ra, rb, .. }
+        | SubtractWithBorrow { ra, rb, .. }
+        | And { ra, rb, .. }
+        | Or { ra, rb, .. }
+        | Xor { ra, rb, .. }
+        | Compare { ra, rb }
+        | CompareLowBytes { ra, rb }
+        | CompareHighBytes { ra, rb } => [Some(ra), Some(rb)],
+        Not { ra, .. } => [Some(ra), None],
+        LogicalLeftShift { rs, .. }
+        | LogicalRightShift { rs, .. }
+        | ArithmeticRightShift { rs, .. }
+        | RotateRight { rs, .. }
+        | CompareImmediateWithLowByte { rs, .. }
+        | CompareImmediateWithHighByte { rs, .. }
+        | LoadWord { rs, .. }
+        | JumpToPointer { rs } => [Some(rs), None],
+        LoadToLowByte { rd, rs } | LoadToHighByte { rd, rs } => [Some(rd), Some(rs)],
+        StoreWord { rs, rd } | StoreFromLowByte { rs, rd } | StoreFromHighByte { rs, rd } => {
+            [Some(rs), Some(rd)]
+        }
+        AddImmediate { rd, .. }
+        | SubImmediate { rd, .. }
+        | MoveImmediateToLowByte { rd, .. }
+        | MoveImmediateToHighByte { rd, .. } => [Some(rd), None],
+        JumpToOffset { .. }
+        | BranchIfCarry { .. }
+        | BranchIfNotCarry { .. }
+        | BranchIfOverflow { .. }
+        | BranchIfNotOverflow { .. }
+        | BranchIfZero { .. }
+        | BranchIfNotZero { .. }
+        | BranchIfSigned { .. }
+        | BranchIfNotSigned { .. }
+        | Halt => [None, None],
+    }
+}
+
+fn add(a: u16, b: u16, carry: bool) -> (u16, Flags) {
+    let (result, carry1) = a.overflowing_add(b);
+    let (result, carry2) = result.overflowing_add(carry as u16);
+    let flags = Flags {
+        carry: carry1 | carry2,
+        overflow: (a ^ result) & (b ^ result) & 0x8000 != 0,
+        zero: result == 0,
+        signed: result & 0x8000 != 0,
+    };
+    (result, flags)
+}
+
+fn subtract(a: u16, b: u16, borrow: bool) -> (u16, Flags) {
+    let (result, borrow1) = a.overflowing_sub(b);
+    let (result, borrow2) = result.overflowing_sub(borrow as u16);
+    let flags = Flags {
+        carry: borrow1 | borrow2,
+        overflow: (a ^ b) & (a ^ result) & 0x8000 != 0,
+        zero: result == 0,
+        signed: result & 0x8000 != 0,
+    };
+    (result, flags)
+}
+
+fn compare_bytes(a: u8, b: u8) -> Flags {
+    let (result, borrow) = a.overflowing_sub(b);
+    Flags {
+        carry: borrow,
+        overflow: (a ^ b) & (a ^ result) & 0x80 != 0,
+        zero: result == 0,
+        signed: result & 0x80 != 0,
+    }
+}
+
+fn logic(result: u16, carry: bool) -> (u16, Flags) {
+    let flags = Flags {
+        carry,
+        overflow: false,
+        zero: result == 0,
+        signed: result & 0x8000 != 0,
+    };
+    (result, flags)
+}
+
+impl Pipeline {
+    pub fn new(cpu: &Cpu) -> Pipeline {
+        Pipeline {
+            fetch_address: cpu.program_counter,
+            ..Pipeline::default()
+        }
+    }
+
+    pub fn is_done(&self, cpu: &Cpu) -> bool {
+        cpu.halted
+            && self.fetched.is_none()
+            && self.decoded.is_none()
+            && self.executed.is_none()
+            && self.accessed.is_none()
+    }
+
+    pub fn cycle(&mut self, cpu: &mut Cpu) -> Result<()> {
+        self.stats.cycles += 1;
+        cpu.cycles += 1;
+
+        self.write_back(cpu)?;
+        if let Some(latch) = self.executed.take() {
+            let latch = self.access(cpu, latch);
+            self.accessed = Some(latch);
+        }
+
+        if let Some(decoded) = self.decoded.take() {
+            if let (Ok(instruction), Some(latch)) = (&decoded.instruction, &self.accessed)
+                && matches!(
+                    latch.access,
+                    Access::LoadWord(_) | Access::LoadLowByte(_) | Access::LoadHighByte(_)
+                )
+                && reads(instruction).contains(&latch.destination)
+            {
+                self.decoded = Some(decoded);
+                self.stats.stalls += 1;
+                return Ok(());
+            }
+            let latch = self.execute(cpu, decoded);
+            let next = latch.next;
+            let redirect = next != latch.address.wrapping_add(2);
+            if latch.fault.is_some() || matches!(latch.instruction, Instruction::Halt) {
+                self.stopped = true;
+                self.fetched = None;
+            }
+            self.executed = Some(latch);
+            if redirect && !self.stopped {
+                self.advance(cpu);
+                self.stats.flushes +=
+                    self.decoded.take().is_some() as u64 + self.fetched.take().is_some() as u64;
+                self.fetch_address = next;
+                return Ok(());
+            }
+        }
+        self.advance(cpu);
+        Ok(())
+    }
+
+    fn advance(&mut self, cpu: &Cpu) {
+        self.decoded = self.fetched.take().map(|fetched| Decoded {
+            address: fetched.address,
+            instruction: match cpu.fetch_fault(fetched.address) {
+                Some(fault) => Err(fault),
+                None => Cpu::decode(fetched.word).map_err(|error| error.to_string()),
+            },
+        });
+        if !self.stopped {
+            self.fetched = Some(Fetched {
+                address: self.fetch_address,
+                word: cpu.read_word(self.fetch_address),
+            });
+            self.fetch_address = self.fetch_address.wrapping_add(2);
+        }
+    }
+
+    fn write_back(&mut self, cpu: &mut Cpu) -> Result<()> {
+        let Some(latch) = self.accessed.take() else {
+            return Ok(());
+        };
+        if let Some(fault) = latch.fault {
+            cpu.program_counter = latch.address;
+            bail!(fault);
+        }
+        if let Some(rd) = latch.destination {
+            cpu.registers[rd] = latch.value;
+        }
+        if let Some(flags) = latch.flags {
+            cpu.flags = flags;
+        }
+        cpu.program_counter = latch.next;
+        cpu.halted = matches!(latch.instruction, Instruction::Halt);
+        self.stats.instructions += 1;
+        cpu.retire(
+            latch.address,
+            latch.instruction,
+            self.stats.cycles - self.retired_at,
+        );
+        self.retired_at = self.stats.cycles;
+        Ok(())
+    }
+
+    fn access(&mut self, cpu: &mut Cpu, mut latch: Latch) -> Latch {
+        let stored = match latch.access {
+            Access::None => return latch,
+            Access::LoadWord(address) => {
+                latch.value = cpu.read_word(address);
+                return latch;
+            }
+            Access::LoadLowByte(address) => {
+                latch.value = (latch.value & 0xff00) | cpu.read_byte(address) as u16;
+                return latch;
+            }
+            Access::LoadHighByte(address) => {
+                latch.value = (latch.value & 0x00ff) | (cpu.read_byte(address) as u16) << 8;
+                return latch;
+            }
+            Access::StoreWord(address, value) => {
+                latch.fault = cpu
+                    .write_fault(address)
+                    .or_else(|| cpu.write_fault(address.wrapping_add(1)));
+                if latch.fault.is_none() {
+                    cpu.write_word(address, value);
+                }
+                [address, address.wrapping_add(1)]
+            }
+            Access::StoreByte(address, value) => {
+                latch.fault = cpu.write_fault(address);
+                if latch.fault.is_none() {
+                    cpu.write_byte(address, value);
+                }
+                [address, address]
+            }
+        };
+        if latch.fault.is_some() {
+            self.stopped = true;
+            self.decoded = None;
+            self.fetched = None;
+            return latch;
+        }
+        let modified =
+            |address: u16| stored.contains(&address) || stored.contains(&address.wrapping_add(1));
+        if self
+            .decoded
+            .as_ref()
+            .is_some_and(|decoded| modified(decoded.address))
+            || self
+                .fetched
+                .is_some_and(|fetched| modified(fetched.address))
+        {
+            self.stats.flushes +=
+                self.decoded.take().is_some() as u64 + self.fetched.take().is_some() as u64;
+            self.fetch_address = latch.next;
+        }
+        latch
+    }
+
+    fn forward(&self, cpu: &Cpu, register: usize) -> u16 {
+        match &self.accessed {
+            Some(latch) if latch.destination == Some(register) => latch.value,
+            _ => cpu.registers[register],
+        }
+    }
+
+    fn forward_flags(&self, cpu: &Cpu) -> Flags {
+        match self.accessed.as_ref().and_then(|latch| latch.flags.clone()) {
+            Some(flags) => flags,
+            None => cpu.flags.clone(),
+        }
+    }
+
+    fn execute(&self, cpu: &Cpu, decoded: Decoded) -> Latch {
+        use Instruction::*;
+
+        let next = decoded.address.wrapping_add(2);
+        let mut latch = Latch {
+            address: decoded.address,
+            next,
+            instruction: Halt,
+            destination: None,
+            value: 0,
+            flags: None,
+            access: Access::None,
+            fault: None,
+        };
+        let instruction = match decoded.instruction {
+            Ok(instruction) => instruction,
+            Err(fault) => {
+                latch.fault = Some(fault);
+                return latch;
+            }
+        };
+        latch.instruction = instruction;
+
+        let read = |register| self.forward(cpu, register);
+        let flags = self.forward_flags(cpu);
+        let branch = |taken: bool, offset: i16| {
+            if taken {
+                next.wrapping_add_signed(offset << 1)
+            } else {
+                next
+            }
+        };
+        let (rd, (value, flags)) = match instruction {
+            Add { rd, ra, rb } => (rd, add(read(ra), read(rb), false)),
+            AddWithCarry { rd, ra, rb } => (rd, add(read(ra), read(rb), flags.carry)),
+            Subtract { rd, ra, rb } => (rd, subtract(read(ra), read(rb), false)),
+            SubtractWithBorrow { rd, ra, rb } => (rd, subtract(read(ra), read(rb), flags.carry)),
+            And { rd, ra, rb } => (rd, logic(read(ra) & read(rb), false)),
+            Or { rd, ra, rb } => (rd, logic(read(ra) | read(rb), false)),
+            Xor { rd, ra, rb } => (rd, logic(read(ra) ^ read(rb), false)),
+            Not { rd, ra } => (rd, logic(!read(ra), false)),
+            LogicalLeftShift { rd, rs, imm } => {
+                let rs = read(rs);
+                (
+                    rd,
+                    logic(rs << imm, imm != 0 && (rs >> (16 - imm)) & 1 != 0),
+                )
+            }
+            LogicalRightShift { rd, rs, imm } => {
+                let rs = read(rs);
+                (rd, logic(rs >> imm, imm != 0 && (rs >> (imm - 1)) & 1 != 0))
+            }
+            ArithmeticRightShift { rd, rs, imm } => {
+                let rs = read(rs);
+                let result = ((rs as i16) >> imm) as u16;
+                (rd, logic(result, imm != 0 && (rs >> (imm - 1)) & 1 != 0))
+            }
+            RotateRight { rd, rs, imm } => {
+                let result = read(rs).rotate_right(imm as u32);
+                (rd, logic(result, imm != 0 && result & 0x8000 != 0))
+            }
+            AddImmediate { rd, imm } => (rd, add(read(rd), imm, false)),
+            SubImmediate { rd, imm } => (rd, subtract(read(rd), imm, false)),
+            Compare { ra, rb } => {
+                latch.flags = Some(subtract(read(ra), read(rb), false).1);
+                return latch;
+            }
+            CompareLowBytes { ra, rb } => {
+                latch.flags = Some(compare_bytes(read(ra) as u8, read(rb) as u8));
+                return latch;
+            }
+            CompareHighBytes { ra, rb } => {
+                let (ra, rb) = ((read(ra) >> 8) as u8, (read(rb) >> 8) as u8);
+                latch.flags = Some(compare_bytes(ra, rb));
+                return latch;
+            }
+            CompareImmediateWithLowByte { rs, imm } => {
+                latch.flags = Some(compare_bytes(read(rs) as u8, imm as u8));
+                return latch;
+            }
+            CompareImmediateWithHighByte { rs, imm } => {
+                latch.flags = Some(compare_bytes((read(rs) >> 8) as u8, imm as u8));
+                return latch;
+            }
+            MoveImmediateToLowByte { rd, imm } => {
+                latch.destination = Some(rd);
+                latch.value = (read(rd) & 0xff00) | imm;
+                return latch;
+            }
+            MoveImmediateToHighByte { rd, imm } => {
+                latch.destination = Some(rd);
+                latch.value = (read(rd) & 0x00ff) | imm << 8;
+                return latch;
+            }
+            LoadWord { rd, rs } => {
+                latch.destination = Some(rd);
+                latch.access = Access::LoadWord(read(rs));
+                return latch;
+            }
+            LoadToLowByte { rd, rs } => {
+                latch.destination = Some(rd);
+                latch.value = read(rd);
+                latch.access = Access::LoadLowByte(read(rs));
+                return latch;
+            }
+            LoadToHighByte { rd, rs } => {
+                latch.destination = Some(rd);
+                latch.value = read(rd);
+                latch.access = Access::LoadHighByte(read(rs));
+                return latch;
+            }
+            StoreWord { rs, rd } => {
+                latch.access = Access::StoreWord(read(rd), read(rs));
+                return latch;
+            }
+            StoreFromLowByte { rs, rd } => {
+                latch.access = Access::StoreByte(read(rd), read(rs) as u8);
+                return latch;
+            }
+            StoreFromHighByte { rs, rd } => {
+                latch.access = Access::StoreByte(read(rd), (read(rs) >> 8) as u8);
+                return latch;
+            }
+            JumpToOffset { offset } => {
+                latch.next = branch(true, offset);
+                return latch;
+            }
+            JumpToPointer { rs } => {
+                latch.next = read(rs);
+                return latch;
+            }
+            BranchIfCarry { offset } => {
+                latch.next = branch(flags.carry, offset);
+                return latch;
+            }
+            BranchIfNotCarry { offset } => {
+                latch.next = branch(!flags.carry, offset);
+                return latch;
+            }
+            BranchIfOverflow { offset } => {
+                latch.next = branch(flags.overflow, offset);
+                return latch;
+            }
+            BranchIfNotOverflow { offset } => {
+                latch.next = branch(!flags.overflow, offset);
+                return latch;
+            }
+            BranchIfZero { offset } => {
+                latch.next = branch(flags.zero, offset);
+                return latch;
+            }
+            BranchIfNotZero { offset } => {
+                latch.next = branch(!flags.zero, offset);
+                return latch;
+            }
+            BranchIfSigned { offset } => {
+                latch.next = branch(flags.signed, offset);
+                return latch;
+            }
+            BranchIfNotSigned { offset } => {
+                latch.next = branch(!flags.signed, offset);
+                return latch;
+            }
+            Halt => return latch,
+        };
+        latch.destination = Some(rd);
+        latch.value = value;
+        latch.flags = Some(flags);
+        latch
+    }
+}
@@ -0,0 +1,161 @@
+use crate::executable::Symbol;
+use crate::instructions::Instruction;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+const HOT_SPOTS: usize = 10;
+
+#[derive(Default)]
+pub struct Profile {
+    pub counts: BTreeMap<u16, u64>,
+    pub classes: BTreeMap<&'static str, u64>,
+    pub cycles: BTreeMap<&'static str, u64>,
+    pub branches: BTreeMap<u16, (u64, u64)>,
+    stacks: HashMap<Vec<u16>, u64>,
+    stack: Vec<u16>,
+}
+
+fn label_at(address: u16, symbols: &[Symbol]) -> Option<&Symbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.address <= address)
+        .max_by_key(|symbol| symbol.address)
+}
+
+fn label_name(address: u16, symbols: &[Symbol]) -> String {
+    label_at(address, symbols).map_or(String::from("?"), |symbol| symbol.name.clone())
+}
+
+fn describe(address: u16, symbols: &[Symbol]) -> String {
+    match label_at(address, symbols) {
+        Some(symbol) if symbol.address == address => symbol.name.clone(),
+        Some(symbol) => format!("{}+{}", symbol.name, address - symbol.address),
+        None => format!("0x{address:04x}"),
+    }
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    count as f64 * 100.0 / total.max(1) as f64
+}
+
+impl Profile {
+    pub fn record(
+        &mut self,
+        address: u16,
+        instruction: Instruction,
+        cycles: u64,
+        next: u16,
+        call: bool,
+        ret: bool,
+    ) {
+        *self.counts.entry(address).or_default() += 1;
+        *self.classes.entry(instruction.class()).or_default() += 1;
+        *self.cycles.entry(instruction.class()).or_default() += cycles;
+        if instruction.class() == "branch" {
+            let (taken, not_taken) = self.branches.entry(address).or_default();
+            if next == address.wrapping_add(2) {
+                *not_taken += 1;
+            } else {
+                *taken += 1;
+            }
+        }
+
+        if self.stack.is_empty() {
+            self.stack.push(address);
+        }
+        match self.stacks.get_mut(&self.stack[..]) {
+            Some(count) => *count += cycles,
+            None => {
+                self.stacks.insert(self.stack.clone(), cycles);
+            }
+        }
+        if call {
+            self.stack.push(next);
+        } else if ret && self.stack.len() > 1 {
+            self.stack.pop();
+        }
+    }
+
+    pub fn report(&self, symbols: &[Symbol], memory: &[u8]) -> String {
+        let total: u64 = self.counts.values().sum();
+        let mut result = String::new();
+
+        let mut hot_spots = self.counts.iter().collect::<Vec<_>>();
+        hot_spots.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let _ = writeln!(result, "hot spots:");
+        for (&address, &count) in hot_spots.into_iter().take(HOT_SPOTS) {
+            let word = u16::from_le_bytes([
+                memory[address as usize],
+                memory[address.wrapping_add(1) as usize],
+            ]);
+            let instruction = Instruction::decode(word).map_or(String::from("?"), |instruction| {
+                instruction.to_assembly(address)
+            });
+            let _ = writeln!(
+                result,
+                "  {count:>10} {:>6.2}%  0x{address:04x}  {:<20} {instruction}",
+                percent(count, total),
+                describe(address, symbols)
+            );
+        }
+
+        let mut labels = BTreeMap::<String, u64>::new();
+        for (&address, &count) in &self.counts {
+            let label =
+                label_at(address, symbols).map_or(String::from("?"), |symbol| symbol.name.clone());
+            *labels.entry(label).or_default() += count;
+        }
+        let mut labels = labels.into_iter().collect::<Vec<_>>();
+        labels.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let _ = writeln!(result, "\nlabels:");
+        for (label, count) in labels {
+            let _ = writeln!(
+                result,
+                "  {count:>10} {:>6.2}%  {label}",
+                percent(count, total)
+            );
+        }
+
+        let mut classes = self.classes.iter().collect::<Vec<_>>();
+        classes.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let _ = writeln!(result, "\ninstruction classes:");
+        let total_cycles: u64 = self.cycles.values().sum();
+        for (class, &count) in classes {
+            let cycles = self.cycles.get(class).copied().unwrap_or(0);
+            let _ = writeln!(
+                result,
+                "  {count:>10} {:>6.2}%  {cycles:>10} cycles {:>6.2}%  {class}",
+                percent(count, total),
+                percent(cycles, total_cycles)
+            );
+        }
+
+        let _ = writeln!(result, "\nbranches:");
+        for (&address, &(taken, not_taken)) in &self.branches {
+            let _ = writeln!(
+                result,
+                "  0x{address:04x}  {:<20} taken {taken}, not taken {not_taken}",
+                describe(address, symbols)
+            );
+        }
+
+        let _ = writeln!(result, "\n{total} instructions, {total_cycles} cycles");
+        result
+    }
+
+    pub fn folded(&self, symbols: &[Symbol]) -> String {
+        let mut lines = self
+            .stacks
+            .iter()
+            .map(|(stack, count)| {
+                let frames = stack
+                    .iter()
+                    .map(|&address| label_name(address, symbols))
+                    .collect::<Vec<_>>();
+                format!("{} {count}", frames.join(";"))
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines.join("\n") + "\n"
+    }
+}
@@ -0,0 +1,110 @@
+use crate::assembler::{AssemblerOptions, Define, assemble_to_executable, assemble_to_object};
+use crate::executable::IsaProfile;
+use crate::formats::{OutputFormat, write_image};
+use crate::layout::{Bank, Layout};
+use crate::linker::{default_placements, link};
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST: &str = "risc16.toml";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    project: Project,
+    layout: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    defines: BTreeMap<String, i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Project {
+    name: String,
+    sources: Vec<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    profile: Option<String>,
+    #[serde(default)]
+    formats: Vec<String>,
+    #[serde(default)]
+    output: Option<String>,
+}
+
+pub fn build(manifest: &str) -> Result<()> {
+    let text =
+        fs::read_to_string(manifest).with_context(|| format!("unable to read {manifest}"))?;
+    let Manifest {
+        project,
+        layout,
+        defines,
+    } = toml::from_str(&text).with_context(|| format!("invalid manifest {manifest}"))?;
+    let root = Path::new(manifest).parent().unwrap_or(Path::new(""));
+    let path = |relative: &str| root.join(relative).to_string_lossy().to_string();
+
+    if project.sources.is_empty() {
+        bail!("no sources listed in {manifest}");
+    }
+
+    let layout = match layout {
+        Some(banks) => {
+            let banks = banks
+                .iter()
+                .map(|(name, range)| format!("{name}={range}").parse())
+                .collect::<Result<Vec<Bank>>>()?;
+            Layout::new(banks)?
+        }
+        None => Layout::default(),
+    };
+    let options = AssemblerOptions {
+        layout,
+        include_dirs: project.include.iter().map(|dir| path(dir)).collect(),
+        defines: defines
+            .into_iter()
+            .map(|(name, value)| Define { name, value })
+            .collect(),
+        ..AssemblerOptions::default()
+    };
+
+    let mut executable = if let [source] = project.sources.as_slice() {
+        assemble_to_executable(&path(source), &options)?
+    } else {
+        let objects = project
+            .sources
+            .iter()
+            .map(|source| {
+                assemble_to_object(&path(source), &options)
+                    .with_context(|| format!("unable to assemble {source}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        link(&objects, &default_placements(&options.layout))?
+    };
+    if let Some(profile) = &project.profile {
+        executable.profile = profile.parse::<IsaProfile>()?;
+    }
+
+    let output = path(project.output.as_deref().unwrap_or("build"));
+    fs::create_dir_all(&output).with_context(|| format!("unable to create {output}"))?;
+
+    let formats = if project.formats.is_empty() {
+        vec![OutputFormat::Rx]
+    } else {
+        project
+            .formats
+            .iter()
+            .map(|format| format.parse())
+            .collect::<Result<Vec<OutputFormat>>>()?
+    };
+    for format in formats {
+        let file = Path::new(&output).join(format!("{}.{}", project.name, format.extension()));
+        fs::write(&file, write_image(&executable, &format))
+            .with_context(|| format!("unable to write {}", file.display()))?;
+        println!("{}", file.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::emulator::{Cpu, Flags};
+use crate::utils::Reader;
+use anyhow::{Result, bail};
+
+pub const MAGIC: &[u8; 4] = b"RS16";
+pub const VERSION: u8 = 1;
+
+pub struct Snapshot {
+    pub registers: [u16; 8],
+    pub flags: Flags,
+    pub program_counter: u16,
+    pub halted: bool,
+    pub cycles: u64,
+    pub memory: Vec<u8>,
+}
+
+impl Snapshot {
+    pub fn capture(cpu: &Cpu) -> Snapshot {
+        Snapshot {
+            registers: cpu.registers,
+            flags: cpu.flags.clone(),
+            program_counter: cpu.program_counter,
+            halted: cpu.halted,
+            cycles: cpu.cycles,
+            memory: cpu.memory.to_vec(),
+        }
+    }
+
+    pub fn restore(&self, cpu: &mut Cpu) {
+        cpu.registers = self.registers;
+        cpu.flags = self.flags.clone();
+        cpu.program_counter = self.program_counter;
+        cpu.halted = self.halted;
+        cpu.cycles = self.cycles;
+        cpu.memory.copy_from_slice(&self.memory);
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        for register in self.registers {
+            bytes.extend_from_slice(&register.to_le_bytes());
+        }
+        bytes.push(
+            self.flags.carry as u8
+                | (self.flags.overflow as u8) << 1
+                | (self.flags.zero as u8) << 2
+                | (self.flags.signed as u8) << 3,
+        );
+        bytes.extend_from_slice(&self.program_counter.to_le_bytes());
+        bytes.push(self.halted as u8);
+        bytes.extend_from_slice(&self.cycles.to_le_bytes());
+        bytes.extend_from_slice(&self.memory);
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != MAGIC {
+            bail!("not a RISC16 snapshot");
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            bail!("unsupported snapshot version {version}");
+        }
+        let mut registers = [0; 8];
+        for register in &mut registers {
+            *register = reader.u16()?;
+        }
+        let flags = reader.u8()?;
+        let flags = Flags {
+            carry: flags & 1 != 0,
+            overflow: flags & 2 != 0,
+            zero: flags & 4 != 0,
+            signed: flags & 8 != 0,
+        };
+        let program_counter = reader.u16()?;
+        let halted = reader.u8()? != 0;
+        let cycles = reader.u64()?;
+        let memory = reader.take(0x10000)?.to_vec();
+
+        Ok(Snapshot {
+            registers,
+            flags,
+            program_counter,
+            halted,
+            cycles,
+            memory,
+        })
+    }
+}
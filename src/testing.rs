@@ -0,0 +1,266 @@
+use crate::assembler::{AssemblerOptions, assemble_to_executable};
+use crate::coverage::Coverage;
+use crate::emulator::Cpu;
+use crate::executable::Executable;
+use crate::profile::Profile;
+use crate::timing::Timing;
+use crate::utils::parse_u32;
+use anyhow::{Context, Result, anyhow, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TEST_SUFFIX: &str = ".test.asm";
+
+pub struct TestOptions {
+    pub cycles: u64,
+    pub timing: Timing,
+    pub coverage: Option<String>,
+    pub assembler: AssemblerOptions,
+}
+
+enum Target {
+    Register(usize),
+    Memory { start: String, end: Option<String> },
+}
+
+struct Expectation {
+    line: usize,
+    text: String,
+    target: Target,
+    values: Vec<i64>,
+}
+
+fn parse_value(input: &str) -> Result<i64> {
+    let input = input.trim();
+    match input.strip_prefix('-') {
+        Some(magnitude) => Ok(-(parse_u32(magnitude.trim())? as i64)),
+        None => Ok(parse_u32(input)? as i64),
+    }
+}
+
+fn parse_expectation(text: &str) -> Result<(Target, Vec<i64>)> {
+    let Some((target, values)) = text.split_once("==") else {
+        bail!("expected an expectation like r2 == 0x0037");
+    };
+    let target = target.trim();
+    let values = values
+        .split(',')
+        .map(parse_value)
+        .collect::<Result<Vec<_>>>()
+        .context("invalid expected value")?;
+
+    let target = if let Some(range) = target
+        .strip_prefix("mem[")
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        match range.split_once("..") {
+            Some((start, end)) => Target::Memory {
+                start: start.trim().to_string(),
+                end: Some(end.trim().to_string()),
+            },
+            None => Target::Memory {
+                start: range.trim().to_string(),
+                end: None,
+            },
+        }
+    } else if let Some(register) = target
+        .strip_prefix('r')
+        .and_then(|register| register.parse::<usize>().ok())
+        .filter(|register| *register < 8)
+    {
+        if values.len() != 1 {
+            bail!("expected a single value for r{register}");
+        }
+        Target::Register(register)
+    } else {
+        bail!("invalid expectation target {target}");
+    };
+
+    let (name, range) = match target {
+        Target::Register(_) => ("word", -0x8000..=0xffff),
+        Target::Memory { .. } => ("byte", -0x80..=0xff),
+    };
+    if let Some(value) = values.iter().find(|value| !range.contains(*value)) {
+        bail!("expected value {value} does not fit in a {name}");
+    }
+    Ok((target, values))
+}
+
+fn read_expectations(path: &Path) -> Result<Vec<Expectation>> {
+    let source =
+        fs::read_to_string(path).with_context(|| format!("unable to read {}", path.display()))?;
+    let mut expectations = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let Some((_, comment)) = line.split_once(';') else {
+            continue;
+        };
+        let Some(text) = comment.trim().strip_prefix("expect ") else {
+            continue;
+        };
+        let (target, values) =
+            parse_expectation(text).with_context(|| format!("line {}", index + 1))?;
+        expectations.push(Expectation {
+            line: index + 1,
+            text: text.trim().to_string(),
+            target,
+            values,
+        });
+    }
+    Ok(expectations)
+}
+
+fn resolve_address(input: &str, executable: &Executable) -> Result<u16> {
+    let (name, offset) = match input.split_once('+') {
+        Some((name, offset)) => (name.trim(), parse_value(offset)?),
+        None => (input, 0),
+    };
+    let base = match executable.symbol(name) {
+        Some(address) => address as i64,
+        None => parse_value(name).with_context(|| format!("unknown address {name}"))?,
+    };
+    u16::try_from(base + offset).with_context(|| format!("address {input} out of range"))
+}
+
+fn check(expectation: &Expectation, cpu: &Cpu, executable: &Executable) -> Result<Option<String>> {
+    let (actual, expected) = match &expectation.target {
+        Target::Register(register) => {
+            let expected = expectation.values[0] as u16;
+            (
+                format!("0x{:04x}", cpu.registers[*register]),
+                format!("0x{expected:04x}"),
+            )
+        }
+        Target::Memory { start, end } => {
+            let start = resolve_address(start, executable)? as usize;
+            let end = match end {
+                Some(end) => resolve_address(end, executable)? as usize,
+                None => start + 1,
+            };
+            if end < start || end - start != expectation.values.len() {
+                bail!(
+                    "line {}: expected {} values, got {}",
+                    expectation.line,
+                    end.saturating_sub(start),
+                    expectation.values.len()
+                );
+            }
+            let format = |bytes: &mut dyn Iterator<Item = u8>| {
+                bytes
+                    .map(|byte| byte.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            (
+                format(&mut cpu.memory[start..end].iter().copied()),
+                format(&mut expectation.values.iter().map(|value| *value as u8)),
+            )
+        }
+    };
+    Ok((actual != expected).then(|| {
+        format!(
+            "line {}: expected {}, got {actual}",
+            expectation.line, expectation.text
+        )
+    }))
+}
+
+fn run_test(
+    path: &Path,
+    options: &TestOptions,
+    coverage: Option<&mut Coverage>,
+) -> Result<Vec<String>> {
+    let expectations = read_expectations(path)?;
+    let input = path.to_str().context("invalid test path")?;
+    let executable = assemble_to_executable(input, &options.assembler)?;
+
+    let mut cpu = Cpu::with_layout(&options.assembler.layout);
+    cpu.load(&executable.segments, 0)?;
+    cpu.program_counter = executable.entry;
+    cpu.timing = options.timing.clone();
+    if coverage.is_some() {
+        cpu.profile = Some(Profile::default());
+    }
+
+    let mut result = Ok(());
+    while !cpu.halted {
+        if cpu.cycles >= options.cycles {
+            result = Err(anyhow!("cycle limit of {} reached", options.cycles));
+            break;
+        }
+        if let Err(error) = cpu.step() {
+            result = Err(error);
+            break;
+        }
+    }
+    if let (Some(coverage), Some(profile)) = (coverage, &cpu.profile) {
+        coverage.add(&executable, profile, &options.assembler.layout);
+    }
+    result?;
+
+    let mut failures = Vec::new();
+    for expectation in &expectations {
+        failures.extend(check(expectation, &cpu, &executable)?);
+    }
+    Ok(failures)
+}
+
+fn discover(path: &Path, tests: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_file() {
+        tests.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(path)
+        .with_context(|| format!("unable to read {}", path.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            discover(&entry, tests)?;
+        } else if entry
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(TEST_SUFFIX))
+        {
+            tests.push(entry);
+        }
+    }
+    Ok(())
+}
+
+pub fn run_tests(paths: Vec<String>, options: TestOptions) -> Result<()> {
+    let mut tests = Vec::new();
+    for path in &paths {
+        discover(Path::new(path), &mut tests)?;
+    }
+
+    let mut coverage = options.coverage.as_ref().map(|_| Coverage::default());
+    let mut failed = 0;
+    for test in &tests {
+        match run_test(test, &options, coverage.as_mut()) {
+            Ok(failures) if failures.is_empty() => println!("PASS {}", test.display()),
+            Ok(failures) => {
+                failed += 1;
+                println!("FAIL {}", test.display());
+                for failure in failures {
+                    println!("    {failure}");
+                }
+            }
+            Err(error) => {
+                failed += 1;
+                println!("FAIL {}", test.display());
+                println!("    {error:#}");
+            }
+        }
+    }
+
+    println!("{} passed, {failed} failed", tests.len() - failed);
+    if let (Some(coverage), Some(path)) = (&coverage, &options.coverage) {
+        fs::write(path, coverage.lcov()).with_context(|| format!("unable to write {path}"))?;
+        print!("\n{}", coverage.summary());
+    }
+    if failed > 0 {
+        bail!("{failed} of {} tests failed", tests.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::instructions::Instruction;
+use anyhow::{Context, Result};
+use std::fs;
+use std::str::FromStr;
+
+pub const CLASSES: &[&str] = &[
+    "alu",
+    "shift",
+    "immediate",
+    "compare",
+    "move",
+    "load",
+    "store",
+    "jump",
+    "branch",
+    "halt",
+];
+
+#[derive(Clone, Debug)]
+pub struct Latency {
+    pub name: String,
+    pub cycles: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    pub classes: [u64; CLASSES.len()],
+    pub taken: u64,
+    pub memory: u64,
+}
+
+impl FromStr for Latency {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let Some((name, cycles)) = s.split_once('=') else {
+            anyhow::bail!("expected a latency like load=2");
+        };
+        Ok(Latency {
+            name: name.trim().to_string(),
+            cycles: cycles.trim().parse().context("invalid latency")?,
+        })
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing {
+            classes: [1; CLASSES.len()],
+            taken: 0,
+            memory: 0,
+        }
+    }
+}
+
+impl FromStr for Timing {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut timing = Timing::default();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split(['#', ';']).next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            timing
+                .set(&line.parse()?)
+                .with_context(|| format!("line {}", index + 1))?;
+        }
+        Ok(timing)
+    }
+}
+
+impl Timing {
+    pub fn load(path: Option<&str>, latencies: &[Latency]) -> Result<Timing> {
+        let mut timing = match path {
+            Some(path) => fs::read_to_string(path)
+                .with_context(|| format!("unable to read {path}"))?
+                .parse()
+                .with_context(|| format!("invalid timing {path}"))?,
+            None => Timing::default(),
+        };
+        for latency in latencies {
+            timing.set(latency)?;
+        }
+        Ok(timing)
+    }
+
+    pub fn set(&mut self, latency: &Latency) -> Result<()> {
+        match latency.name.as_str() {
+            "taken" => self.taken = latency.cycles,
+            "memory" => self.memory = latency.cycles,
+            name => match CLASSES.iter().position(|class| *class == name) {
+                Some(class) => self.classes[class] = latency.cycles,
+                None => anyhow::bail!("unknown instruction class {name}"),
+            },
+        }
+        Ok(())
+    }
+
+    pub fn cycles(&self, instruction: &Instruction, taken: bool) -> u64 {
+        let class = instruction.class();
+        let mut cycles = CLASSES
+            .iter()
+            .position(|name| *name == class)
+            .map_or(1, |class| self.classes[class]);
+        if taken {
+            cycles += self.taken;
+        }
+        if matches!(class, "load" | "store") {
+            cycles += self.memory;
+        }
+        cycles
+    }
+}
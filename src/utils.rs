@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 pub fn parse_u16(input: &str) -> Result<u16> {
     if let Some(hex) = input.strip_prefix("0x") {
@@ -9,3 +9,62 @@ pub fn parse_u16(input: &str) -> Result<u16> {
         Ok(input.parse::<u16>()?)
     }
 }
+
+pub fn parse_u32(input: &str) -> Result<u32> {
+    if let Some(hex) = input.strip_prefix("0x") {
+        Ok(u32::from_str_radix(hex, 16)?)
+    } else if let Some(bin) = input.strip_prefix("0b") {
+        Ok(u32::from_str_radix(bin, 2)?)
+    } else {
+        Ok(input.parse::<u32>()?)
+    }
+}
+
+pub fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend_from_slice(&(string.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    pub fn take(&mut self, size: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + size)
+            .context("unexpected end of file")?;
+        self.position += size;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into()?))
+    }
+
+    pub fn string(&mut self) -> Result<String> {
+        let size = self.u16()? as usize;
+        Ok(String::from_utf8(self.take(size)?.to_vec())?)
+    }
+}
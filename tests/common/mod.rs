@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+use risc16::assembler::{AssemblerOptions, assemble_to_executable};
+use risc16::emulator::Cpu;
+use risc16::executable::Executable;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("risc16-{}-{name}", std::process::id()))
+}
+
+pub fn assemble(path: &str) -> Executable {
+    assemble_to_executable(path, &AssemblerOptions::default())
+        .unwrap_or_else(|error| panic!("{path}: {error:#}"))
+}
+
+pub fn assemble_source(name: &str, source: &str) -> Executable {
+    let path = temp_path(&format!("{name}.asm"));
+    fs::write(&path, source).unwrap();
+    let executable = assemble_to_executable(path.to_str().unwrap(), &AssemblerOptions::default());
+    fs::remove_file(&path).unwrap();
+    executable.unwrap_or_else(|error| panic!("{error:#}\n{source}"))
+}
+
+pub fn load(executable: &Executable) -> Cpu {
+    let mut cpu = Cpu::with_layout(&AssemblerOptions::default().layout);
+    cpu.load(&executable.segments, 0).unwrap();
+    cpu.program_counter = executable.entry;
+    cpu
+}
+
+pub fn run(executable: &Executable) -> Cpu {
+    let mut cpu = load(executable);
+    cpu.run().unwrap();
+    cpu
+}
+
+pub fn emulate_source(name: &str, source: &str, args: &[&str]) -> Output {
+    let path = temp_path(&format!("{name}.asm"));
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_risc16"))
+        .arg("emulate")
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
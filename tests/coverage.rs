@@ -0,0 +1,94 @@
+mod common;
+
+use risc16::assembler::AssemblerOptions;
+use risc16::testing::{TestOptions, run_tests};
+use risc16::timing::Timing;
+use std::fs;
+
+#[test]
+fn lcov_report() {
+    let directory = common::temp_path("coverage");
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(
+        directory.join("abs.asm"),
+        "#bank code\nabs:\n TST r1\n BNS .done\n NEG r1\n.done:\n RET\n",
+    )
+    .unwrap();
+    fs::write(
+        directory.join("abs.test.asm"),
+        "; expect r1 == 5\n#bank code\n MOV r1, 5\n CALL abs\n HLT\n#include \"abs.asm\"\n",
+    )
+    .unwrap();
+    let lcov = directory.join("lcov.info");
+
+    run_tests(
+        vec![directory.to_str().unwrap().to_string()],
+        TestOptions {
+            cycles: 1000,
+            timing: Timing::default(),
+            coverage: Some(lcov.to_str().unwrap().to_string()),
+            assembler: AssemblerOptions::default(),
+        },
+    )
+    .unwrap();
+
+    let report = fs::read_to_string(&lcov).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    let record = report
+        .split("end_of_record\n")
+        .find(|record| record.contains("abs.asm\n"))
+        .unwrap();
+    let lines = record.lines().collect::<Vec<_>>();
+    for expected in [
+        "DA:3,1",
+        "DA:4,1",
+        "DA:5,0",
+        "DA:7,1",
+        "BRDA:4,0,0,1",
+        "BRDA:4,0,1,0",
+        "LF:4",
+        "LH:3",
+        "BRF:2",
+        "BRH:1",
+    ] {
+        assert!(
+            lines.contains(&expected),
+            "{expected} missing from\n{record}"
+        );
+    }
+}
+
+#[test]
+fn library_not_attributed_to_caller() {
+    let directory = common::temp_path("coverage-library");
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(
+        directory.join("mul.test.asm"),
+        "; expect r1 == 6\n#bank code\n LI r1, 2\n LI r2, 3\n CALL mul16\n HLT\n#include \"<std>/lib/mul.asm\"\n",
+    )
+    .unwrap();
+    let lcov = directory.join("lcov.info");
+
+    run_tests(
+        vec![directory.to_str().unwrap().to_string()],
+        TestOptions {
+            cycles: 1000,
+            timing: Timing::default(),
+            coverage: Some(lcov.to_str().unwrap().to_string()),
+            assembler: AssemblerOptions::default(),
+        },
+    )
+    .unwrap();
+
+    let report = fs::read_to_string(&lcov).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(report.matches("SF:").count(), 1, "{report}");
+    assert!(!report.contains("<std>"), "{report}");
+    assert!(!report.contains("BRDA:"), "{report}");
+    for expected in ["DA:6,1", "LF:4", "LH:4", "BRF:0"] {
+        assert!(
+            report.lines().any(|line| line == expected),
+            "{expected} missing from\n{report}"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use risc16::assembler::{AssemblerOptions, Define, assemble_to_executable};
+use std::fs;
+
+const SOURCE: &str = "#bank code\n#if DEBUG != 0 {\n MOV r1, SIZE\n}\n#if DEBUG == 0 {\n MOV r1, 1\n}\n MOV r2, SIZE\n HLT\n";
+
+fn run(defines: &[&str]) -> [u16; 2] {
+    let path = common::temp_path(&format!("defines-{}.asm", defines.join("-")));
+    fs::write(&path, SOURCE).unwrap();
+    let options = AssemblerOptions {
+        defines: defines
+            .iter()
+            .map(|define| define.parse().unwrap())
+            .collect(),
+        ..AssemblerOptions::default()
+    };
+    let executable = assemble_to_executable(path.to_str().unwrap(), &options);
+    fs::remove_file(&path).unwrap();
+    let cpu = common::run(&executable.unwrap());
+    [cpu.registers[1], cpu.registers[2]]
+}
+
+#[test]
+fn defines_select_configurations() {
+    assert_eq!(run(&["DEBUG", "SIZE=0x40"]), [0x40, 0x40]);
+    assert_eq!(run(&["DEBUG=0", "SIZE=64"]), [1, 64]);
+    assert_eq!(run(&["DEBUG=-1", "SIZE=0b101"]), [5, 5]);
+}
+
+#[test]
+fn parses_defines() {
+    let define: Define = "BUFFER_SIZE=-0x10".parse().unwrap();
+    assert_eq!((define.name.as_str(), define.value), ("BUFFER_SIZE", -16));
+    assert_eq!("DEBUG".parse::<Define>().unwrap().value, 1);
+    for bad in ["=1", "1ST=1", "A-B=1", "SIZE=ten", "SIZE=0xg"] {
+        assert!(bad.parse::<Define>().is_err(), "{bad}");
+    }
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use risc16::emulator::Dump;
+
+const PROGRAM: &str = "#bank code
+_start:
+    HLT
+#bank data
+text: #d \"Hi!\\0\"
+words: #d8 0x34, 0x12, 0xfe, 0xff
+";
+
+fn dump(name: &str, args: &[&str]) -> String {
+    let output = common::emulate_source(name, PROGRAM, args);
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn parse_dumps() {
+    assert!(matches!(
+        "0xfff0:0xffff".parse(),
+        Ok(Dump::Range {
+            start: 0xfff0,
+            end: 0xffff
+        })
+    ));
+    assert!(matches!("data".parse(), Ok(Dump::Bank(name)) if name == "data"));
+    assert!("0x10:0x0f".parse::<Dump>().is_err());
+    assert!("0x10:0x10000".parse::<Dump>().is_err());
+    assert!("x:0x10".parse::<Dump>().is_err());
+}
+
+#[test]
+fn hex_rows() {
+    let output = dump("dump-hex", &["--dump", "0x5000:0x5007"]);
+    assert!(output.contains("5000: 48 69 21 00 34 12 fe ff "));
+    assert!(output.trim_end().ends_with("|Hi!.4...|"));
+}
+
+#[test]
+fn word_rows() {
+    let output = dump(
+        "dump-u16",
+        &["--dump", "0x5000:0x5007", "--memory-format", "u16"],
+    );
+    assert!(output.contains("5000: 26952    33  4660 65534 "));
+    let output = dump(
+        "dump-i16",
+        &["--dump", "0x5000:0x5007", "--memory-format", "i16"],
+    );
+    assert!(output.contains("5000:  26952     33   4660     -2 "));
+}
+
+#[test]
+fn end_of_memory() {
+    let output = dump("dump-end", &["--dump", "0xfff0:0xffff"]);
+    assert_eq!(
+        output
+            .lines()
+            .filter(|line| line.starts_with("fff0: "))
+            .count(),
+        1
+    );
+    assert!(output.contains(&format!("fff0: {}|", "00 ".repeat(16))));
+    let output = dump(
+        "dump-memory-end",
+        &["-m", "--memory-start", "0xfff0", "--memory-end", "0xffff"],
+    );
+    assert!(output.contains(&format!("fff0: {}|", "00 ".repeat(16))));
+}
+
+#[test]
+fn unknown_bank() {
+    let output = common::emulate_source("dump-unknown", PROGRAM, &["--dump", "missing"]);
+    assert!(!output.status.success());
+}
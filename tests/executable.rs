@@ -0,0 +1,59 @@
+mod common;
+
+use risc16::executable::{Executable, describe};
+
+const SOURCE: &str =
+    "#bank code\nloop:\n JMP _start\n_start:\n MOV r1, value\n HLT\n#bank data\nvalue: #d8 7\n";
+
+#[test]
+fn container_round_trip() {
+    let executable = common::assemble_source("rx", SOURCE);
+    assert_eq!(executable.entry, 2);
+    assert_eq!(executable.symbol("value"), Some(0x5000));
+
+    let read = Executable::from_bytes(&executable.to_bytes()).unwrap();
+    assert_eq!(read.entry, executable.entry);
+    let segments = |executable: &Executable| {
+        executable
+            .segments
+            .iter()
+            .map(|segment| (segment.address, segment.data.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(segments(&read), segments(&executable));
+    assert_eq!(read.symbol("loop"), Some(0));
+    assert_eq!(read.symbol("_start"), Some(2));
+    assert_eq!(read.files, executable.files);
+    let lines = |executable: &Executable| {
+        executable
+            .lines
+            .iter()
+            .map(|entry| (entry.address, entry.size, entry.file, entry.line))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lines(&read), lines(&executable));
+    assert!(lines(&read).contains(&(2, 4, 0, 5)));
+    assert!(lines(&read).contains(&(6, 2, 0, 6)));
+
+    let cpu = common::run(&read);
+    assert_eq!(cpu.registers[1], 0x5000);
+}
+
+#[test]
+fn describes_container() {
+    let text = describe(&common::assemble_source("rx-info", SOURCE));
+    assert!(text.contains("entry:    0x0002\n"));
+    assert!(text.contains("  0x0000..0x0008  8 bytes\n"));
+    assert!(text.contains("  0x5000  value\n"));
+}
+
+#[test]
+fn rejects_bad_containers() {
+    let bytes = common::assemble_source("rx-bad", SOURCE).to_bytes();
+    let error = |bytes: &[u8]| format!("{:#}", Executable::from_bytes(bytes).unwrap_err());
+    assert!(error(b"RIFF\x01\x00").contains("not a RISC16 executable"));
+    let mut version = bytes.clone();
+    version[4] = 9;
+    assert!(error(&version).contains("unsupported executable version 9"));
+    assert!(Executable::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
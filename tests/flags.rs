@@ -0,0 +1,290 @@
+use risc16::emulator::Cpu;
+
+const EDGES: &[u16] = &[
+    0x0000, 0x0001, 0x0002, 0x007f, 0x0080, 0x00ff, 0x0100, 0x5555, 0x7ffe, 0x7fff, 0x8000, 0x8001,
+    0xaaaa, 0xff00, 0xff7f, 0xff80, 0xfffe, 0xffff,
+];
+
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    result: u16,
+    carry: bool,
+    overflow: bool,
+    zero: bool,
+    signed: bool,
+}
+
+fn values() -> Vec<u16> {
+    let mut values = EDGES.to_vec();
+    let mut state = 0x2545_f491_u32;
+    for _ in 0..64 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        values.push(state as u16);
+    }
+    values
+}
+
+fn execute(word: u16, a: u16, b: u16, carry: bool) -> Outcome {
+    let mut cpu = Cpu::default();
+    cpu.memory[..2].copy_from_slice(&word.to_le_bytes());
+    cpu.registers[1] = a;
+    cpu.registers[2] = b;
+    cpu.registers[3] = 0x1234;
+    cpu.flags.carry = carry;
+    cpu.flags.overflow = !carry;
+    cpu.flags.zero = carry;
+    cpu.flags.signed = !carry;
+    cpu.step().unwrap();
+    Outcome {
+        result: cpu.registers[3],
+        carry: cpu.flags.carry,
+        overflow: cpu.flags.overflow,
+        zero: cpu.flags.zero,
+        signed: cpu.flags.signed,
+    }
+}
+
+fn execute_on_rd(word: u16, a: u16, carry: bool) -> Outcome {
+    let mut cpu = Cpu::default();
+    cpu.memory[..2].copy_from_slice(&word.to_le_bytes());
+    cpu.registers[3] = a;
+    cpu.flags.carry = carry;
+    cpu.step().unwrap();
+    Outcome {
+        result: cpu.registers[3],
+        carry: cpu.flags.carry,
+        overflow: cpu.flags.overflow,
+        zero: cpu.flags.zero,
+        signed: cpu.flags.signed,
+    }
+}
+
+fn arithmetic(result: i64, signed: i64, bits: u32) -> Outcome {
+    let mask = (1i64 << bits) - 1;
+    let value = (result & mask) as u16;
+    Outcome {
+        result: value,
+        carry: !(0..=mask).contains(&result),
+        overflow: !(-(1i64 << (bits - 1))..(1i64 << (bits - 1))).contains(&signed),
+        zero: value == 0,
+        signed: value >> (bits - 1) & 1 != 0,
+    }
+}
+
+fn logic(result: u16, carry: bool) -> Outcome {
+    Outcome {
+        result,
+        carry,
+        overflow: false,
+        zero: result == 0,
+        signed: result & 0x8000 != 0,
+    }
+}
+
+fn compare(outcome: Outcome) -> Outcome {
+    Outcome {
+        result: 0x1234,
+        ..outcome
+    }
+}
+
+fn register(funct: u16) -> u16 {
+    (3 << 9) | (1 << 6) | (2 << 3) | funct
+}
+
+#[test]
+fn register_arithmetic() {
+    let values = values();
+    for &a in &values {
+        for &b in &values {
+            for carry in [false, true] {
+                let c = carry as i64;
+                let (ua, ub) = (a as i64, b as i64);
+                let (sa, sb) = (a as i16 as i64, b as i16 as i64);
+
+                let expected = arithmetic(ua + ub, sa + sb, 16);
+                assert_eq!(
+                    execute(register(0b000), a, b, carry),
+                    expected,
+                    "ADD {a:#x} {b:#x}"
+                );
+                let expected = arithmetic(ua + ub + c, sa + sb + c, 16);
+                assert_eq!(
+                    execute(register(0b001), a, b, carry),
+                    expected,
+                    "ADC {a:#x} {b:#x} {carry}"
+                );
+                let expected = arithmetic(ua - ub, sa - sb, 16);
+                assert_eq!(
+                    execute(register(0b010), a, b, carry),
+                    expected,
+                    "SUB {a:#x} {b:#x}"
+                );
+                let expected = arithmetic(ua - ub - c, sa - sb - c, 16);
+                assert_eq!(
+                    execute(register(0b011), a, b, carry),
+                    expected,
+                    "SBB {a:#x} {b:#x} {carry}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn register_logic() {
+    let values = values();
+    for &a in &values {
+        for &b in &values {
+            for carry in [false, true] {
+                assert_eq!(
+                    execute(register(0b100), a, b, carry),
+                    logic(a & b, false),
+                    "AND"
+                );
+                assert_eq!(
+                    execute(register(0b101), a, b, carry),
+                    logic(a | b, false),
+                    "OR"
+                );
+                assert_eq!(
+                    execute(register(0b110), a, b, carry),
+                    logic(a ^ b, false),
+                    "XOR"
+                );
+                assert_eq!(
+                    execute(register(0b111), a, b, carry),
+                    logic(!a, false),
+                    "NOT"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn shifts_and_rotates() {
+    for a in values() {
+        for imm in 0..16u32 {
+            for carry in [false, true] {
+                let word = |funct: u16| 0x1000 | (3 << 9) | (1 << 6) | ((imm as u16) << 2) | funct;
+                let wide = a as u32;
+                let shifted_out = |bit: u32| (wide >> bit) & 1 != 0;
+
+                let expected = logic((wide << imm) as u16, imm != 0 && shifted_out(16 - imm));
+                assert_eq!(
+                    execute(word(0b00), a, 0, carry),
+                    expected,
+                    "SHL {a:#x} {imm}"
+                );
+                let expected = logic((wide >> imm) as u16, imm != 0 && shifted_out(imm - 1));
+                assert_eq!(
+                    execute(word(0b01), a, 0, carry),
+                    expected,
+                    "SHR {a:#x} {imm}"
+                );
+                let extended = a as i16 as i32;
+                let expected = logic((extended >> imm) as u16, imm != 0 && shifted_out(imm - 1));
+                assert_eq!(
+                    execute(word(0b10), a, 0, carry),
+                    expected,
+                    "SAR {a:#x} {imm}"
+                );
+                let rotated = ((wide | wide << 16) >> imm) as u16;
+                let expected = logic(rotated, imm != 0 && shifted_out(imm - 1));
+                assert_eq!(
+                    execute(word(0b11), a, 0, carry),
+                    expected,
+                    "ROR {a:#x} {imm}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn immediate_arithmetic() {
+    for a in values() {
+        for imm in 0..=0xffu16 {
+            for carry in [false, true] {
+                let word = |funct: u16| 0x2000 | (3 << 9) | (imm << 1) | funct;
+                let (ua, sa, i) = (a as i64, a as i16 as i64, imm as i64);
+                let expected = arithmetic(ua + i, sa + i, 16);
+                assert_eq!(
+                    execute_on_rd(word(0), a, carry),
+                    expected,
+                    "ADDI {a:#x} {imm}"
+                );
+                let expected = arithmetic(ua - i, sa - i, 16);
+                assert_eq!(
+                    execute_on_rd(word(1), a, carry),
+                    expected,
+                    "SUBI {a:#x} {imm}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn comparisons() {
+    let values = values();
+    for &a in &values {
+        for &b in &values {
+            let word = (1 << 9) | (2 << 6);
+            let expected = arithmetic(a as i64 - b as i64, a as i16 as i64 - b as i16 as i64, 16);
+            assert_eq!(
+                execute(0x3000 | word, a, b, false),
+                compare(expected),
+                "CMP"
+            );
+        }
+    }
+    for a in 0..=0xffu16 {
+        for b in 0..=0xffu16 {
+            let expected = compare(arithmetic(
+                a as i64 - b as i64,
+                a as u8 as i8 as i64 - b as u8 as i8 as i64,
+                8,
+            ));
+            let word = 0x3000 | (1 << 9) | (2 << 6);
+            assert_eq!(
+                execute(word | 0b10, 0xa500 | a, 0x5a00 | b, true),
+                expected,
+                "CMPL"
+            );
+            assert_eq!(
+                execute(word | 0b11, a << 8 | 0xa5, b << 8 | 0x5a, true),
+                expected,
+                "CMPH"
+            );
+            let word = 0x4000 | (1 << 9) | (b << 1);
+            assert_eq!(execute(word, 0xa500 | a, 0, true), expected, "CMPL imm");
+            assert_eq!(
+                execute(word | 1, a << 8 | 0xa5, 0, true),
+                expected,
+                "CMPH imm"
+            );
+        }
+    }
+}
+
+#[test]
+fn moves_preserve_flags() {
+    for carry in [false, true] {
+        let before = execute(0x5000 | (3 << 9), 0, 0, carry);
+        assert_eq!(before.result, 0x1200);
+        assert_eq!(
+            (before.carry, before.overflow, before.zero, before.signed),
+            (carry, !carry, carry, !carry)
+        );
+        let before = execute(0x5001 | (3 << 9) | (0xab << 1), 0, 0, carry);
+        assert_eq!(before.result, 0xab34);
+        assert_eq!(
+            (before.carry, before.overflow, before.zero, before.signed),
+            (carry, !carry, carry, !carry)
+        );
+    }
+}
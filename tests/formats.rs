@@ -0,0 +1,80 @@
+use risc16::executable::Executable;
+use risc16::formats::{OutputFormat, Segment, read_image, write_image};
+
+fn memory(executable: &Executable) -> Vec<u8> {
+    let mut memory = vec![0; 0x10000];
+    for segment in &executable.segments {
+        let start = segment.address as usize;
+        memory[start..start + segment.data.len()].copy_from_slice(&segment.data);
+    }
+    memory
+}
+
+fn sample() -> Executable {
+    Executable::from_segments(vec![
+        Segment {
+            address: 0x0000,
+            data: (0..40).collect(),
+        },
+        Segment {
+            address: 0x0101,
+            data: vec![0xaa; 5],
+        },
+        Segment {
+            address: 0x5000,
+            data: vec![0x12, 0x34, 0x00, 0x00, 0xff],
+        },
+    ])
+}
+
+#[test]
+fn images_round_trip() {
+    let executable = sample();
+    for format in ["bin", "ihex", "srec", "memh", "logisim", "rx"] {
+        let format: OutputFormat = format.parse().unwrap();
+        let image = write_image(&executable, &format);
+        let read = read_image(&image, &format).unwrap();
+        assert!(
+            memory(&read) == memory(&executable),
+            "{}",
+            format.extension()
+        );
+    }
+}
+
+#[test]
+fn sparse_formats_keep_addresses() {
+    let executable = sample();
+    for format in ["ihex", "srec"] {
+        let format: OutputFormat = format.parse().unwrap();
+        let read = read_image(&write_image(&executable, &format), &format).unwrap();
+        let addresses = read
+            .segments
+            .iter()
+            .map(|segment| segment.address)
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, [0x0000, 0x0101, 0x5000]);
+    }
+}
+
+#[test]
+fn rejects_bad_images() {
+    let error = |text: &str, format: &str| {
+        let format: OutputFormat = format.parse().unwrap();
+        format!("{:#}", read_image(text.as_bytes(), &format).unwrap_err())
+    };
+    assert!(error(":0100000001FF\n", "ihex").contains("invalid checksum"));
+    assert!(
+        error(":020000040001F9\n", "ihex")
+            .contains("line 1: extended address does not fit in 16 bits")
+    );
+    assert!(error(":020000021000EC\n", "ihex").contains("line 1: segment address does not fit"));
+    assert!(error("S2090000000102030405E7\n", "srec").contains("does not fit in 16 bits"));
+    assert!(
+        error("v2.0 raw\n1 2\n99999999999*0\n", "logisim")
+            .contains("line 3: image does not fit in memory")
+    );
+    assert!(error("v2.0 raw\nx*0\n", "logisim").contains("line 2: invalid repeat count"));
+    assert!(error("v2.0 raw\n32768*1 1\n", "logisim").contains("does not fit in memory"));
+    assert!(read_image(b"v2.0 raw\n32768*1\n", &"logisim".parse().unwrap()).is_ok());
+}
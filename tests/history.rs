@@ -0,0 +1,117 @@
+mod common;
+
+use risc16::emulator::{AbiCheck, Cpu, History};
+
+fn load(capacity: usize) -> (Cpu, u16) {
+    let executable = common::assemble("examples/memcopy.asm");
+    let mut cpu = common::load(&executable);
+    cpu.history = Some(History::new(capacity));
+    (cpu, executable.symbol("destination").unwrap())
+}
+
+#[test]
+fn step_back_to_reset() {
+    let (mut cpu, _) = load(1 << 16);
+    let registers = cpu.registers;
+    let memory = cpu.memory;
+    cpu.run().unwrap();
+    let mut steps = 0;
+    while cpu.step_back() {
+        steps += 1;
+    }
+    assert_eq!(steps, 102);
+    assert!(!cpu.halted);
+    assert_eq!(cpu.program_counter, 0);
+    assert_eq!(cpu.registers, registers);
+    assert_eq!(cpu.memory, memory);
+}
+
+#[test]
+fn reverse_continue_to_write() {
+    let (mut cpu, destination) = load(1 << 16);
+    cpu.run().unwrap();
+    assert_eq!(cpu.memory[destination as usize + 5], 13);
+
+    let store = cpu.reverse_continue(destination + 5).unwrap();
+    assert_eq!(cpu.program_counter, store);
+    assert_eq!(cpu.memory[destination as usize + 5], 0);
+    assert_eq!(cpu.memory[destination as usize + 4], 11);
+    assert_eq!(cpu.registers[2], destination + 5);
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.memory[destination as usize + 5], 13);
+    assert_eq!(cpu.reverse_continue(destination + 0x10), None);
+}
+
+#[test]
+fn bounded_history() {
+    let (mut cpu, _) = load(10);
+    cpu.run().unwrap();
+    let mut steps = 0;
+    while cpu.step_back() {
+        steps += 1;
+    }
+    assert_eq!(steps, 10);
+    assert_eq!(cpu.history.as_ref().unwrap().len(), 0);
+}
+
+#[test]
+fn fault_keeps_faulting_instruction() {
+    let executable = common::assemble_source(
+        "history-fault",
+        "#bank code\n_start:\n    LI r1, -1\n    MOV r2, 0x1234\n    STW r2, [r1]\n    HLT\n",
+    );
+    let mut cpu = common::load(&executable);
+    cpu.protect = true;
+    cpu.history = Some(History::new(16));
+    while cpu.step().is_ok() {}
+    let store = cpu.program_counter;
+    let steps = cpu.history.as_ref().unwrap().len();
+    let memory = cpu.memory;
+    assert!(cpu.step().is_err());
+    assert_eq!(cpu.program_counter, store);
+    assert_eq!(cpu.history.as_ref().unwrap().len(), steps);
+    assert_eq!(cpu.memory, memory);
+    assert_eq!(cpu.memory[0xffff], 0);
+
+    cpu.protect = false;
+    cpu.step().unwrap();
+    assert_eq!(cpu.memory[0xffff], 0x34);
+    assert!(cpu.step_back());
+    assert_eq!(cpu.program_counter, store);
+    assert_eq!(cpu.memory[0xffff], 0);
+}
+
+#[test]
+fn step_back_over_abi_violation() {
+    let executable = common::assemble_source(
+        "history-abi",
+        "#bank code\n_start:\n CALL clobber\n HLT\nclobber:\n MOV r4, 1\n RET\n",
+    );
+    let mut cpu = common::load(&executable);
+    cpu.abi_check = Some(AbiCheck::default());
+    cpu.history = Some(History::new(16));
+    let violations = |cpu: &Cpu| cpu.abi_check.as_ref().unwrap().violations.len();
+
+    cpu.run().unwrap();
+    assert_eq!(violations(&cpu), 1);
+    assert!(cpu.step_back() && cpu.step_back());
+    assert_eq!(violations(&cpu), 0);
+    cpu.run().unwrap();
+    assert_eq!(violations(&cpu), 1);
+
+    while cpu.step_back() {}
+    cpu.run().unwrap();
+    assert_eq!(violations(&cpu), 1);
+}
+
+#[test]
+fn debugger_rejects_profiling() {
+    let output = common::emulate_source(
+        "history-profile",
+        "#bank code\n_start:\n HLT\n",
+        &["--step", "--profile"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--profile or --folded"));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use risc16::instructions::{INSTRUCTIONS, Instruction, reference};
+use std::fs;
+
+#[test]
+fn readme_matches_reference() {
+    let readme = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md")).unwrap();
+    let table = readme
+        .split_once("<!-- isa -->\n")
+        .and_then(|(_, rest)| rest.split_once("<!-- /isa -->"))
+        .map(|(table, _)| table)
+        .unwrap();
+    assert_eq!(table, reference(), "run `risc16 isa` to update the README");
+}
+
+#[test]
+fn definitions_do_not_overlap() {
+    for (index, definition) in INSTRUCTIONS.iter().enumerate() {
+        assert_eq!(
+            definition.pattern & !definition.mask,
+            0,
+            "{}",
+            definition.name
+        );
+        let decoded = Instruction::decode(definition.pattern).unwrap();
+        assert_eq!(decoded.definition().name, definition.name);
+        for other in &INSTRUCTIONS[..index] {
+            let mask = definition.mask & other.mask;
+            assert_ne!(
+                definition.pattern & mask,
+                other.pattern & mask,
+                "{} overlaps {}",
+                definition.name,
+                other.name
+            );
+        }
+    }
+}
+
+#[test]
+fn jump_to_pointer() {
+    let executable = common::assemble_source(
+        "isa-jmp",
+        "#bank code\n MOV r3, target\n JMP [r3]\n HLT\ntarget:\n MOV r1, 1\n HLT\n",
+    );
+    let data = &executable.segments[0].data;
+    let word = u16::from_le_bytes([data[4], data[5]]);
+    assert_eq!(word, Instruction::JumpToPointer { rs: 3 }.encode());
+    assert_eq!(word, 0x9600);
+    assert_eq!(common::run(&executable).registers[1], 1);
+}
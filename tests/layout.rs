@@ -0,0 +1,82 @@
+mod common;
+
+use risc16::assembler::{AssemblerOptions, assemble_to_executable};
+use risc16::layout::{Bank, Layout};
+use std::fs;
+
+const STORE: &str = "#bank code\n MOV r1, 0x0200\n MOV r2, 0x55\n STL r2, [r1]\n HLT\n";
+
+#[test]
+fn parses_layouts() {
+    let layout: Layout =
+        "code=0x0000:0x8000 ; program\n\ndata=0x8000:0x7000\nstack=0xf000:0x1000\n"
+            .parse()
+            .unwrap();
+    assert_eq!(layout.banks.len(), 3);
+    assert_eq!(layout.bank("stack").unwrap().end(), 0x10000);
+
+    let moved = Layout::load(None, &["data=0x6000:0x1000".parse::<Bank>().unwrap()]).unwrap();
+    assert_eq!(moved.bank("data").unwrap().address, 0x6000);
+    assert!(
+        moved
+            .bankdefs()
+            .contains("#bankdef data\n{\n    #addr 0x6000\n    #size 0x1000\n")
+    );
+    assert!(!moved.bankdefs().contains("#outp 8 * 0xe000"));
+
+    let error = |banks: &[&str]| {
+        let banks: Vec<Bank> = banks.iter().map(|bank| bank.parse().unwrap()).collect();
+        format!("{:#}", Layout::load(None, &banks).unwrap_err())
+    };
+    assert!(error(&["extra=0x4800:0x100"]).contains("bank extra overlaps bank rodata"));
+    assert!(error(&["extra=0xff00:0x200"]).contains("does not fit in memory"));
+    assert!(error(&["extra=0xff00:0"]).contains("bank extra is empty"));
+}
+
+#[test]
+fn assembles_into_layout() {
+    let path = common::temp_path("layout.asm");
+    fs::write(
+        &path,
+        "#bank code\n_start:\n MOV r1, value\n HLT\n#bank data\nvalue: #d8 1\n",
+    )
+    .unwrap();
+    let options = AssemblerOptions {
+        layout: Layout::load(None, &["data=0x6000:0x1000".parse().unwrap()]).unwrap(),
+        ..AssemblerOptions::default()
+    };
+    let executable = assemble_to_executable(path.to_str().unwrap(), &options);
+    fs::remove_file(&path).unwrap();
+    let executable = executable.unwrap();
+    assert_eq!(executable.symbol("value"), Some(0x6000));
+    assert_eq!(common::run(&executable).registers[1], 0x6000);
+}
+
+#[test]
+fn protection_is_opt_in() {
+    let executable = common::assemble_source("layout-store", STORE);
+    let cpu = common::run(&executable);
+    assert_eq!(cpu.memory[0x0200], 0x55);
+
+    let mut cpu = common::load(&executable);
+    cpu.protect = true;
+    let error = cpu.run().unwrap_err();
+    assert_eq!(error.to_string(), "write to read-only bank code at 0x0200");
+}
+
+#[test]
+fn runs_raw_images_anywhere() {
+    let image = common::assemble_source("layout-raw", STORE).segments;
+    let raw = |protect: bool| {
+        let mut cpu = common::load(&Default::default());
+        cpu.load(&image, 0x8000).unwrap();
+        cpu.program_counter = 0x8000;
+        cpu.protect = protect;
+        cpu.run().map(|_| cpu.memory[0x0200])
+    };
+    assert_eq!(raw(false).unwrap(), 0x55);
+    assert_eq!(
+        raw(true).unwrap_err().to_string(),
+        "execution outside of code at 0x8000"
+    );
+}
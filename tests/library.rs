@@ -0,0 +1,17 @@
+use risc16::assembler::AssemblerOptions;
+use risc16::testing::{TestOptions, run_tests};
+use risc16::timing::Timing;
+
+#[test]
+fn library_and_example_tests() {
+    run_tests(
+        vec!["arch/lib".to_string(), "examples".to_string()],
+        TestOptions {
+            cycles: 1000000,
+            timing: Timing::default(),
+            coverage: None,
+            assembler: AssemblerOptions::default(),
+        },
+    )
+    .unwrap();
+}
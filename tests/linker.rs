@@ -0,0 +1,75 @@
+mod common;
+
+use risc16::assembler::{AssemblerOptions, assemble_to_object};
+use risc16::linker::{Placement, default_placements, link};
+use risc16::object::{Object, RelocationKind};
+use std::fs;
+
+fn object(name: &str, source: &str) -> Object {
+    let path = common::temp_path(&format!("linker-{name}.asm"));
+    fs::write(&path, source).unwrap();
+    let object = assemble_to_object(path.to_str().unwrap(), &AssemblerOptions::default());
+    fs::remove_file(&path).unwrap();
+    let object = object.unwrap_or_else(|error| panic!("{error:#}\n{source}"));
+    Object::from_bytes(&object.to_bytes()).unwrap()
+}
+
+fn word(executable: &risc16::executable::Executable, address: u16) -> u16 {
+    let memory = common::load(executable).memory;
+    u16::from_le_bytes([memory[address as usize], memory[address as usize + 1]])
+}
+
+const MAIN: &str = "#import double, table\n#bank code\n_start:\n MOV r1, 21\n CALL double\n MOV r2, table\n HLT\n\
+                    #bank data\nflag: #d8 1\npointer: #d le(table`16)\nself: #d le(flag`16)\n";
+const LIBRARY: &str =
+    "#bank code\ndouble:\n ADD r1, r1, r1\n RET\n#bank rodata\ntable: #d8 1, 2, 3\n";
+
+#[test]
+fn links_objects() {
+    let main = object("main", MAIN);
+    let library = object("library", LIBRARY);
+    assert_eq!(main.imports, ["double", "table"]);
+    assert!(
+        main.relocations
+            .iter()
+            .any(|relocation| relocation.kind == RelocationKind::Word && relocation.offset == 1)
+    );
+
+    let layout = AssemblerOptions::default().layout;
+    let executable = link(&[main, library], &default_placements(&layout)).unwrap();
+    let table = executable.symbol("table").unwrap();
+    let flag = executable.symbol("flag").unwrap();
+    assert_eq!(word(&executable, flag + 1), table);
+    assert_eq!(word(&executable, flag + 3), flag);
+
+    let cpu = common::run(&executable);
+    assert_eq!(cpu.registers[1], 42);
+    assert_eq!(cpu.registers[2], table);
+}
+
+#[test]
+fn moves_sections() {
+    let layout = AssemblerOptions::default().layout;
+    let mut placements = default_placements(&layout);
+    for placement in &mut placements {
+        if placement.section == "data" {
+            placement.address = 0x6001;
+        }
+    }
+    let executable = link(
+        &[object("moved-main", MAIN), object("moved-library", LIBRARY)],
+        &placements,
+    )
+    .unwrap();
+    assert_eq!(executable.symbol("flag"), Some(0x6001));
+    assert_eq!(word(&executable, 0x6004), 0x6001);
+    assert!("data=0x6000".parse::<Placement>().is_ok());
+    assert!("data".parse::<Placement>().is_err());
+}
+
+#[test]
+fn reports_missing_symbols() {
+    let layout = AssemblerOptions::default().layout;
+    let error = link(&[object("missing", MAIN)], &default_placements(&layout)).unwrap_err();
+    assert!(format!("{error:#}").contains("double"));
+}
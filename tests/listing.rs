@@ -0,0 +1,61 @@
+mod common;
+
+use risc16::assembler::{AssemblerOptions, assemble_to_listing};
+use std::fs;
+
+#[test]
+fn listing_shows_addresses_bytes_and_expansions() {
+    let path = common::temp_path("listing.asm");
+    fs::write(
+        &path,
+        "#bank code\nstart:\n ADD r1, r2, r3\n MOV r1, 0x1234\n JMP start\n#bank data\nvalue: #d8 0xab\n",
+    )
+    .unwrap();
+    let listing = assemble_to_listing(path.to_str().unwrap(), &AssemblerOptions::default());
+    fs::remove_file(&path).unwrap();
+    let listing = listing.unwrap();
+    let lines = listing.lines().collect::<Vec<_>>();
+
+    let find = |text: &str| {
+        lines
+            .iter()
+            .find(|line| line.contains(text))
+            .unwrap_or_else(|| panic!("{text} missing from\n{listing}"))
+            .split_whitespace()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(find("ADD r1")[..4], ["3", "0000", "98", "02"]);
+    assert_eq!(find("MOV r1, 0x1234")[..2], ["4", "0002"]);
+    assert_eq!(find("+ MOVH r1, 0x12")[..3], ["0002", "25", "52"]);
+    assert_eq!(find("+ MOVL r1, 0x34")[..3], ["0004", "68", "52"]);
+    assert_eq!(find("JMP start")[..4], ["5", "0006", "fc", "8f"]);
+    assert_eq!(find("#d8 0xab")[..3], ["7", "5000", "ab"]);
+}
+
+#[test]
+fn listing_odd_sized_rule() {
+    let path = common::temp_path("listing-odd.asm");
+    fs::write(
+        &path,
+        "#ruledef {\n    BYTES3 => 0x010203\n}\n#bank code\n BYTES3\n HLT\n",
+    )
+    .unwrap();
+    let listing = assemble_to_listing(path.to_str().unwrap(), &AssemblerOptions::default());
+    fs::remove_file(&path).unwrap();
+    let listing = listing.unwrap();
+    let lines = listing.lines().collect::<Vec<_>>();
+
+    assert!(
+        lines.iter().any(|line| line.contains("BYTES3")),
+        "{listing}"
+    );
+    let last = lines
+        .iter()
+        .find(|line| line.contains("+ #d8 0x03"))
+        .unwrap_or_else(|| panic!("odd byte missing from\n{listing}"));
+    assert_eq!(
+        last.split_whitespace().collect::<Vec<_>>()[..2],
+        ["0002", "03"]
+    );
+    assert!(lines.iter().any(|line| line.contains("HLT")), "{listing}");
+}
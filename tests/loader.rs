@@ -0,0 +1,55 @@
+mod common;
+
+use risc16::assembler::AssemblerOptions;
+use risc16::emulator::{InputFormat, Load};
+use risc16::formats::{OutputFormat, write_image};
+use std::fs;
+
+#[test]
+fn loads_images_at_addresses() {
+    let routine = common::assemble_source("loader-routine", "#bank code\n MOV r1, 7\n JMP [r6]\n");
+    let path = common::temp_path("loader-routine.hex");
+    fs::write(&path, write_image(&routine, &OutputFormat::Ihex)).unwrap();
+
+    let load: Load = format!("{}@0x8000", path.display()).parse().unwrap();
+    assert_eq!(load.address, 0x8000);
+    let image = InputFormat::from_path(&load.path).load(&load.path, &AssemblerOptions::default());
+    fs::remove_file(&path).unwrap();
+    let image = image.unwrap();
+
+    let program = common::assemble_source(
+        "loader-main",
+        "#bank code\n MOV r3, 0x8000\n MOV r6, back\n JMP [r3]\nback:\n MOV r2, 1\n HLT\n",
+    );
+    let mut cpu = common::load(&program);
+    cpu.load(&image.segments, load.address).unwrap();
+    cpu.run().unwrap();
+    assert_eq!(cpu.registers[1..3], [7, 1]);
+
+    let error = cpu.load(&image.segments, 0xfffe).unwrap_err();
+    assert!(error.to_string().contains("does not fit in memory"));
+    assert!("routine.bin@later".parse::<Load>().is_err());
+    assert_eq!("routine.bin".parse::<Load>().unwrap().address, 0);
+}
+
+#[test]
+fn loads_every_image_extension() {
+    let program = common::assemble_source("loader-formats", "#bank code\n MOV r1, 0x1234\n HLT\n");
+    for format in [
+        OutputFormat::Bin,
+        OutputFormat::Ihex,
+        OutputFormat::Srec,
+        OutputFormat::Memh,
+        OutputFormat::Logisim,
+        OutputFormat::Rx,
+    ] {
+        let path = common::temp_path(&format!("loader-formats.{}", format.extension()));
+        fs::write(&path, write_image(&program, &format)).unwrap();
+        let path = path.to_str().unwrap();
+        let image = InputFormat::from_path(path).load(path, &AssemblerOptions::default());
+        fs::remove_file(path).unwrap();
+        let image = image.unwrap_or_else(|error| panic!("{path}: {error:#}"));
+        let cpu = common::run(&image);
+        assert_eq!(cpu.registers[1], 0x1234, "{path}");
+    }
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use risc16::emulator::Cpu;
+use risc16::instructions::Instruction;
+
+fn run(name: &str, source: &str) -> Cpu {
+    common::run(&common::assemble_source(&format!("macros-{name}"), source))
+}
+
+fn size(name: &str, source: &str) -> usize {
+    common::assemble_source(&format!("macros-{name}"), source)
+        .segments
+        .iter()
+        .map(|segment| segment.data.len())
+        .sum()
+}
+
+#[test]
+fn load_immediate() {
+    let cpu = run(
+        "li",
+        "#bank code\n LI r1, 0\n LI r2, -1\n LI r3, 0x1234\n LI r4, -2\n LA r5, value\n HLT\n#bank data\nvalue: #d8 0\n",
+    );
+    assert_eq!(cpu.registers[1..6], [0, 0xffff, 0x1234, 0xfffe, 0x5000]);
+    assert_eq!(size("li-zero", "#bank code\n LI r1, 0\n"), 2);
+    assert_eq!(size("li-ones", "#bank code\n LI r1, 0xffff\n"), 2);
+}
+
+#[test]
+fn clear_and_negate() {
+    let cpu = run(
+        "neg",
+        "#bank code\n MOV r1, 5\n NEG r1\n MOV r2, 7\n NEG r3, r2\n CLR r2\n HLT\n",
+    );
+    assert_eq!(cpu.registers[1..4], [0xfffb, 0, 0xfff9]);
+}
+
+#[test]
+fn wide_immediates() {
+    let cpu = run(
+        "addi",
+        "#bank code\n CLR r1\n ADDI r1, 5\n ADDI r1, -3\n LI r2, -1\n ADDI r2, 0x0101\n\
+         MOV r4, 0x7000\n ADDI r4, 1000\n SUBI r4, 2000\n SUBI r4, -1\n\
+         MOV r3, r7\n ADDI r7, -0x100\n ADDI r7, 0x100\n SUB r3, r3, r7\n HLT\n",
+    );
+    assert_eq!(
+        cpu.registers[1..5],
+        [2, 0x0100, 0, 0x7000 + 1000 - 2000 + 1]
+    );
+    let negative = common::assemble_source("addi-negative", "#bank code\n ADDI r1, -1\n");
+    assert_eq!(
+        negative.segments[0].data,
+        Instruction::SubImmediate { rd: 1, imm: 1 }
+            .encode()
+            .to_le_bytes()
+    );
+    assert_eq!(
+        size(
+            "addi-short",
+            "#bank code\n ADDI r1, 0xff\n ADDI r1, -0xff\n"
+        ),
+        4
+    );
+}
+
+#[test]
+fn wide_immediate_flags() {
+    let carry = run(
+        "addi-carry",
+        "#bank code\n LI r1, 0xff00\n LI r2, 0x1234\n ADDI r1, 0x0100\n HLT\n",
+    );
+    assert_eq!(carry.registers[1..3], [0, 0x1234]);
+    assert!(carry.flags.carry && carry.flags.zero);
+
+    let borrow = run(
+        "subi-borrow",
+        "#bank code\n ADDI r7, -0x200\n MOV r1, 0x0100\n SUBI r1, 0x0200\n HLT\n",
+    );
+    assert_eq!(borrow.registers[1], 0xff00);
+    assert!(borrow.flags.carry && borrow.flags.signed);
+    assert_eq!(borrow.registers[7], 0xfe00);
+    assert!(borrow.memory[0xe000..].iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn far_branches() {
+    let source = "#bank code\n CLR r1\n BZ far\n HLT\n#res 1024\nfar:\n MOV r1, 1\n TST r1\n BNZ back\n HLT\nback:\n\
+                  ADDI r1, 1\n HLT\n";
+    assert_eq!(run("far", source).registers[1], 2);
+    assert_eq!(size("near", "#bank code\nloop:\n BZ loop\n"), 2);
+}
+
+#[test]
+fn compare_and_branch() {
+    let cpu = run(
+        "compare",
+        "#bank code\n CLR r4\n LI r1, -5\n LI r2, 3\n\
+         BLT r1, r2, t1\n HLT\nt1: ADDI r4, 1\n\
+         BGE r2, r1, t2\n HLT\nt2: ADDI r4, 2\n\
+         BLTU r2, r1, t3\n HLT\nt3: ADDI r4, 4\n\
+         BGEU r1, r2, t4\n HLT\nt4: ADDI r4, 8\n\
+         LI r1, 0x8000\n LI r2, 1\n\
+         BLT r1, r2, t5\n HLT\nt5: ADDI r4, 16\n\
+         BGE r1, r2, done\n BEQ r1, r1, t6\n HLT\nt6: ADDI r4, 32\n\
+         BNE r1, r1, done\n BLT r1, r2, far\n HLT\n#res 1024\n\
+         far: ADDI r4, 64\n BGE r1, r2, done\n ADDI r4, 128\ndone: HLT\n",
+    );
+    assert_eq!(cpu.registers[4], 0xff);
+}
+
+#[test]
+fn long_jumps() {
+    let source = "#bank code\n CLR r1\n BZ far\n HLT\n#res 5000\nfar:\n ADDI r1, 1\n BLT r0, r1, back\n HLT\n\
+                  #res 5000\nback:\n ADDI r1, 2\n JMP far2\n#res 5000\nfar2:\n ADDI r1, 4\n HLT\n";
+    assert_eq!(run("long", source).registers[1], 7);
+    assert_eq!(size("jmp", "#bank code\nloop:\n#res 4000\n JMP loop\n"), 2);
+    assert_eq!(
+        size("jmp-far", "#bank code\nloop:\n#res 4200\n JMP loop\n"),
+        6
+    );
+}
+
+#[test]
+fn far_calls() {
+    let source = "#bank code\n CLR r1\n CALL far\n ADDI r1, 1\n CALL near\n HLT\n\
+                  near:\n ADDI r1, 4\n JMP skip\n#res 5000\nskip:\n BZ skip\n RET\n\
+                  #res 5000\nfar:\n ADDI r1, 2\n RET\n";
+    let cpu = run("far-call", source);
+    assert_eq!(cpu.registers[1], 7);
+    assert_eq!(
+        size("call-near", "#bank code\n CALL target\ntarget:\n RET\n"),
+        8
+    );
+    assert_eq!(
+        size(
+            "call-far",
+            "#bank code\n CALL target\n#res 4200\ntarget:\n RET\n"
+        ),
+        12
+    );
+}
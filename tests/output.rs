@@ -0,0 +1,62 @@
+mod common;
+
+use serde_json::Value;
+
+const PROGRAM: &str = "#bank code
+_start:
+    LI r1, -2
+    MOV r2, text
+    LDL r3, [r2]
+    HLT
+#bank data
+text: #d \"Hi!\\0\"
+";
+
+fn emulate_json(name: &str, source: &str, args: &[&str]) -> (bool, Value) {
+    let output = common::emulate_source(name, source, &[&["--output", "json"], args].concat());
+    let state = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|error| panic!("{error}: {}", String::from_utf8_lossy(&output.stdout)));
+    (output.status.success(), state)
+}
+
+#[test]
+fn halted_state() {
+    let (success, state) = emulate_json("json-halt", PROGRAM, &["--dump", "0x5000:0x5003"]);
+    assert!(success);
+    assert_eq!(state["halt"], "halt");
+    assert_eq!(state["error"], Value::Null);
+    assert_eq!(state["registers"][1], 0xfffe);
+    assert_eq!(state["registers"][2], 0x5000);
+    assert_eq!(state["registers"][3], b'H');
+    assert_eq!(state["pc"], 12);
+    assert_eq!(state["cycles"], 6);
+    assert_eq!(state["flags"]["zero"], false);
+    assert_eq!(state["memory"][0]["start"], 0x5000);
+    assert_eq!(state["memory"][0]["end"], 0x5003);
+    assert_eq!(
+        state["memory"][0]["bytes"],
+        serde_json::json!([b'H', b'i', b'!', 0])
+    );
+}
+
+#[test]
+fn cycle_limit_state() {
+    let source = "#bank code\n_start:\n    JMP _start\n";
+    let (success, state) = emulate_json("json-limit", source, &["--cycles", "10"]);
+    assert!(success);
+    assert_eq!(state["halt"], "cycle-limit");
+    assert_eq!(state["cycles"], 10);
+}
+
+#[test]
+fn fault_state() {
+    let source = "#bank code\n_start:\n    MOV r1, _start\n    STL r1, [r1]\n    HLT\n";
+    let (success, state) = emulate_json("json-fault", source, &["--protect"]);
+    assert!(!success);
+    assert_eq!(state["halt"], "fault");
+    assert!(
+        state["error"]
+            .as_str()
+            .is_some_and(|error| !error.is_empty())
+    );
+}
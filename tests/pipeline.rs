@@ -0,0 +1,222 @@
+mod common;
+
+use proptest::prelude::*;
+use risc16::emulator::Cpu;
+use risc16::instructions::Instruction;
+use risc16::pipeline::{Pipeline, Stats};
+use std::fs;
+
+const BASE: u16 = 0x2000;
+
+fn run_pipeline(cpu: &mut Cpu, limit: u64) -> Result<Stats, String> {
+    let mut pipeline = Pipeline::new(cpu);
+    while !pipeline.is_done(cpu) {
+        if pipeline.stats.cycles == limit {
+            return Err(String::from("cycle limit"));
+        }
+        pipeline.cycle(cpu).map_err(|error| error.to_string())?;
+    }
+    Ok(pipeline.stats)
+}
+
+fn run_interpreter(cpu: &mut Cpu, limit: u64) -> Result<(), String> {
+    while !cpu.halted {
+        if cpu.cycles == limit {
+            return Err(String::from("cycle limit"));
+        }
+        cpu.step().map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
+
+fn assert_same(interpreter: &Cpu, pipeline: &Cpu) {
+    assert_eq!(interpreter.registers, pipeline.registers);
+    assert_eq!(interpreter.flags, pipeline.flags);
+    assert_eq!(interpreter.program_counter, pipeline.program_counter);
+    assert_eq!(interpreter.halted, pipeline.halted);
+    assert!(interpreter.memory == pipeline.memory, "memory differs");
+}
+
+fn cross_check(name: &str, source: &str) -> (Cpu, Stats) {
+    let executable = common::assemble_source(&format!("pipeline-{name}"), source);
+    let mut interpreter = common::load(&executable);
+    run_interpreter(&mut interpreter, 1_000_000).unwrap();
+    let mut pipeline = common::load(&executable);
+    let stats = run_pipeline(&mut pipeline, 10_000_000).unwrap();
+    assert_same(&interpreter, &pipeline);
+    assert_eq!(stats.instructions, interpreter.cycles);
+    assert_eq!(
+        stats.cycles,
+        stats.instructions + 4 + stats.stalls + stats.flushes
+    );
+    (pipeline, stats)
+}
+
+#[test]
+fn examples() {
+    for example in ["fibonacci", "memcopy"] {
+        let source = fs::read_to_string(format!("examples/{example}.asm")).unwrap();
+        cross_check(example, &source);
+    }
+    let (_, stats) = cross_check(
+        "memcopy-stats",
+        &fs::read_to_string("examples/memcopy.asm").unwrap(),
+    );
+    assert_eq!(stats.stalls, 16);
+    assert_eq!(stats.flushes, 30);
+}
+
+#[test]
+fn library_routines() {
+    let (cpu, stats) = cross_check(
+        "library",
+        "#bank code\n_start:\n LA r4, results\n\
+         LI r1, 1234\n LI r2, 56\n CALL mul16\n\
+         STW r1, [r4]\n ADDI r4, 2\n STW r2, [r4]\n ADDI r4, 2\n\
+         LI r1, -1234\n LI r2, 7\n CALL sdiv16\n\
+         STW r1, [r4]\n ADDI r4, 2\n STW r2, [r4]\n ADDI r4, 2\n\
+         LI r1, -4321\n LA r2, buffer\n CALL itoa\n STW r1, [r4]\n ADDI r4, 2\n\
+         LA r1, copy\n LA r2, buffer\n MOV r3, 8\n CALL memcpy\n\
+         LA r1, buffer\n LA r2, copy\n CALL string_compare\n STW r1, [r4]\n\
+         LA r1, copy\n CALL string_length\n HLT\n\
+         #include \"<std>/lib/mul.asm\"\n#include \"<std>/lib/div.asm\"\n#include \"<std>/lib/itoa.asm\"\n\
+         #include \"<std>/lib/mem.asm\"\n#include \"<std>/lib/string.asm\"\n\
+         #bank bss\nresults: #res 12\nbuffer: #res 8\ncopy: #res 8\n",
+    );
+    assert_eq!(cpu.registers[1], 5);
+    assert_eq!(
+        cpu.memory[0x8000..0x800c],
+        [
+            0xf0, 0x0d, 0x01, 0x00, 0x50, 0xff, 0xfe, 0xff, 0x05, 0x00, 0x00, 0x00
+        ]
+    );
+    assert_eq!(cpu.memory[0x800c..0x8012], *b"-4321\0");
+    assert_eq!(cpu.memory[0x8014..0x801a], *b"-4321\0");
+    assert!(stats.stalls > 0);
+    assert!(stats.flushes > 0);
+}
+
+#[test]
+fn forwarding() {
+    let (cpu, stats) = cross_check(
+        "forwarding",
+        "#bank code\n LI r1, 5\n ADD r2, r1, r1\n SUB r3, r2, r1\n CMP r3, r1\n BZ equal\n\
+         ADDI r4, 1\nequal:\n LA r1, value\n LDW r5, [r1]\n ADD r6, r5, r0\n HLT\n\
+         #bank data\nvalue: #d8 0x34, 0x12\n",
+    );
+    assert_eq!(cpu.registers[2], 10);
+    assert_eq!(cpu.registers[3], 5);
+    assert_eq!(cpu.registers[4], 0);
+    assert_eq!(cpu.registers[6], 0x1234);
+    assert_eq!(stats.stalls, 1);
+    assert_eq!(stats.flushes, 2);
+}
+
+#[test]
+fn self_modifying_code() {
+    let (cpu, stats) = cross_check(
+        "self-modifying",
+        "#bank code\n LA r1, patch\n LA r2, target\n LDW r3, [r1]\n STW r3, [r2]\n\
+         target:\n ADDI r4, 1\n HLT\npatch:\n ADDI r4, 2\n",
+    );
+    assert_eq!(cpu.registers[4], 2);
+    assert_eq!(stats.stalls, 1);
+    assert_eq!(stats.flushes, 2);
+}
+
+#[test]
+fn precise_faults() {
+    let executable = common::assemble_source(
+        "pipeline-fault",
+        "#bank code\n_start:\n LI r1, 7\n MOV r2, _start\n STW r1, [r2]\n ADDI r3, 1\n HLT\n",
+    );
+    let mut interpreter = common::load(&executable);
+    interpreter.protect = true;
+    assert!(run_interpreter(&mut interpreter, 1000).is_err());
+    let mut pipeline = common::load(&executable);
+    pipeline.protect = true;
+    assert!(run_pipeline(&mut pipeline, 1000).is_err());
+    assert_same(&interpreter, &pipeline);
+    assert_eq!(pipeline.registers[3], 0);
+}
+
+#[test]
+fn far_branches_and_calls() {
+    cross_check(
+        "far",
+        "#bank code\n CLR r1\n BZ far\n HLT\n#res 5000\nfar:\n ADDI r1, 1\n BLT r0, r1, back\n HLT\n\
+         #res 5000\nback:\n ADDI r1, 2\n CALL function\n JMP far2\n\
+         function:\n PUSH r6\n LDW r2, [r7]\n ADD r1, r1, r2\n POP r6\n RET\n#res 5000\nfar2:\n ADDI r1, 4\n HLT\n",
+    );
+}
+
+fn instruction() -> impl Strategy<Value = Instruction> {
+    let r = || 0..8usize;
+    let byte = || 0..=0xffu16;
+    let forward = || 0..6i16;
+    prop_oneof![
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::Add { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::SubtractWithBorrow { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::Xor { rd, ra, rb }),
+        (r(), r(), 0..16u16).prop_map(|(rd, rs, imm)| Instruction::RotateRight { rd, rs, imm }),
+        (r(), byte()).prop_map(|(rd, imm)| Instruction::AddImmediate { rd, imm }),
+        (r(), r()).prop_map(|(ra, rb)| Instruction::Compare { ra, rb }),
+        (r(), byte()).prop_map(|(rs, imm)| Instruction::CompareImmediateWithLowByte { rs, imm }),
+        (r(), byte()).prop_map(|(rd, imm)| Instruction::MoveImmediateToHighByte { rd, imm }),
+        (r(), r()).prop_map(|(rd, rs)| Instruction::LoadWord { rd, rs }),
+        (r(), r()).prop_map(|(rd, rs)| Instruction::LoadToHighByte { rd, rs }),
+        (r(), r()).prop_map(|(rs, rd)| Instruction::StoreWord { rs, rd }),
+        (r(), r()).prop_map(|(rs, rd)| Instruction::StoreFromLowByte { rs, rd }),
+        forward().prop_map(|offset| Instruction::JumpToOffset { offset }),
+        forward().prop_map(|offset| Instruction::BranchIfZero { offset }),
+        forward().prop_map(|offset| Instruction::BranchIfNotCarry { offset }),
+        forward().prop_map(|offset| Instruction::BranchIfSigned { offset }),
+    ]
+}
+
+fn load(program: &[Instruction], registers: [u16; 8]) -> Cpu {
+    let mut cpu = Cpu::default();
+    for (index, instruction) in program.iter().enumerate() {
+        let address = BASE as usize + index * 2;
+        let word = match *instruction {
+            Instruction::JumpToOffset { offset } => Instruction::JumpToOffset {
+                offset: offset.min((program.len() - index) as i16),
+            },
+            Instruction::BranchIfZero { offset } => Instruction::BranchIfZero {
+                offset: offset.min((program.len() - index) as i16),
+            },
+            Instruction::BranchIfNotCarry { offset } => Instruction::BranchIfNotCarry {
+                offset: offset.min((program.len() - index) as i16),
+            },
+            Instruction::BranchIfSigned { offset } => Instruction::BranchIfSigned {
+                offset: offset.min((program.len() - index) as i16),
+            },
+            instruction => instruction,
+        }
+        .encode();
+        cpu.memory[address..address + 2].copy_from_slice(&word.to_le_bytes());
+    }
+    let end = BASE as usize + program.len() * 2;
+    cpu.memory[end..end + 2].copy_from_slice(&Instruction::Halt.encode().to_le_bytes());
+    cpu.registers = registers;
+    cpu.program_counter = BASE;
+    cpu
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn random_programs(
+        program in prop::collection::vec(instruction(), 1..48),
+        registers in prop::array::uniform8(any::<u16>()),
+    ) {
+        let mut interpreter = load(&program, registers);
+        let expected = run_interpreter(&mut interpreter, 10_000);
+        prop_assume!(expected.as_ref().err().is_none_or(|error| error != "cycle limit"));
+        let mut pipeline = load(&program, registers);
+        let actual = run_pipeline(&mut pipeline, 100_000);
+        prop_assert_eq!(expected.is_ok(), actual.is_ok(), "{:?} {:?}", expected, actual.map(|_| ()));
+        assert_same(&interpreter, &pipeline);
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use risc16::profile::Profile;
+
+#[test]
+fn profile_calls_and_branches() {
+    let executable = common::assemble_source(
+        "profile",
+        "#bank code\n_start:\n MOV r1, 3\n MOV r2, 5\n CALL square_sum\n HLT\n\
+         square_sum:\n PUSH r6\n CALL mul16\n POP r6\n RET\n#include \"<std>/lib/mul.asm\"\n",
+    );
+    let mut cpu = common::load(&executable);
+    cpu.profile = Some(Profile::default());
+    cpu.run().unwrap();
+    assert_eq!(cpu.registers[1], 15);
+
+    let profile = cpu.profile.unwrap();
+    let total: u64 = profile.counts.values().sum();
+    assert_eq!(total, profile.classes.values().sum::<u64>());
+    assert_eq!(profile.classes["halt"], 1);
+
+    let done = executable.symbol("mul16.done").unwrap();
+    let (&branch, &counts) = profile
+        .branches
+        .iter()
+        .find(|(_, (taken, _))| *taken == 1)
+        .unwrap();
+    assert!(branch < done);
+    assert_eq!(counts, (1, 3));
+
+    let folded = profile.folded(&executable.symbols);
+    let lines = folded.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2].starts_with("_start;square_sum;mul16 "));
+    let sampled: u64 = lines
+        .iter()
+        .map(|line| line.rsplit_once(' ').unwrap().1.parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(sampled, total);
+
+    let report = profile.report(&executable.symbols, &cpu.memory);
+    assert!(report.contains(&format!("{total} instructions")));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use risc16::assembler::{AssemblerOptions, assemble_to_executable};
+use risc16::executable::Executable;
+use risc16::project::build;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = common::temp_path(name);
+    let _ = fs::remove_dir_all(&root);
+    for (path, text) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+    root
+}
+
+fn path(root: &Path, relative: &str) -> String {
+    root.join(relative).to_str().unwrap().to_string()
+}
+
+#[test]
+fn resolves_includes() {
+    let root = tree(
+        "includes",
+        &[
+            (
+                "src/main.asm",
+                "#include \"util/helpers.asm\"\n#include \"math.asm\"\n#bank code\n_start:\n MOV r1, HELPER\n MOV r2, MATH\n HLT\n",
+            ),
+            (
+                "src/util/helpers.asm",
+                "#include \"values.asm\"\nHELPER = VALUE + 1\n",
+            ),
+            ("src/util/values.asm", "VALUE = 10\n"),
+            ("lib/math.asm", "MATH = 42\n"),
+        ],
+    );
+    let main = path(&root, "src/main.asm");
+
+    assert!(assemble_to_executable(&main, &AssemblerOptions::default()).is_err());
+
+    let options = AssemblerOptions {
+        include_dirs: vec![path(&root, "lib")],
+        ..AssemblerOptions::default()
+    };
+    let executable = assemble_to_executable(&main, &options);
+    fs::remove_dir_all(&root).unwrap();
+    let cpu = common::run(&executable.unwrap());
+    assert_eq!(cpu.registers[1..3], [11, 42]);
+}
+
+#[test]
+fn builds_manifests() {
+    let root = tree(
+        "manifest",
+        &[
+            (
+                "risc16.toml",
+                "[project]\nname = \"demo\"\nsources = [\"src/main.asm\", \"src/lib.asm\"]\ninclude = [\"include\"]\nformats = [\"rx\", \"hex\"]\n\n[defines]\nSTART = 5\n",
+            ),
+            (
+                "src/main.asm",
+                "#import double\n#include \"start.asm\"\n#bank code\n_start:\n MOV r1, INITIAL\n CALL double\n HLT\n",
+            ),
+            (
+                "src/lib.asm",
+                "#bank code\ndouble:\n ADD r1, r1, r1\n RET\n",
+            ),
+            ("include/start.asm", "INITIAL = START * 2\n"),
+        ],
+    );
+    let result = build(&path(&root, "risc16.toml"));
+    let rx = fs::read(root.join("build/demo.rx"));
+    let hex = root.join("build/demo.hex").exists();
+    fs::remove_dir_all(&root).unwrap();
+    result.unwrap();
+    assert!(hex);
+
+    let executable = Executable::from_bytes(&rx.unwrap()).unwrap();
+    assert_eq!(common::run(&executable).registers[1], 20);
+}
+
+#[test]
+fn rejects_bad_manifests() {
+    let root = tree(
+        "bad-manifest",
+        &[
+            ("empty.toml", "[project]\nname = \"demo\"\nsources = []\n"),
+            (
+                "unknown.toml",
+                "[project]\nname = \"demo\"\nsources = [\"a.asm\"]\nextra = 1\n",
+            ),
+        ],
+    );
+    let empty = build(&path(&root, "empty.toml")).unwrap_err();
+    let unknown = build(&path(&root, "unknown.toml")).unwrap_err();
+    fs::remove_dir_all(&root).unwrap();
+    assert!(format!("{empty:#}").contains("no sources listed"));
+    assert!(format!("{unknown:#}").contains("invalid manifest"));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use proptest::prelude::*;
+use risc16::assembler::{AssemblerOptions, assemble_to_binary};
+use risc16::emulator::Cpu;
+use risc16::instructions::Instruction;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const BASE: u16 = 0x2000;
+
+static CASE: AtomicUsize = AtomicUsize::new(0);
+
+fn instruction() -> impl Strategy<Value = Instruction> {
+    let r = || 0..8usize;
+    let byte = || 0..=0xffu16;
+    let shift = || 0..16u16;
+    let branch = || -256..256i16;
+    prop_oneof![
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::Add { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::AddWithCarry { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::Subtract { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::SubtractWithBorrow { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::And { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::Or { rd, ra, rb }),
+        (r(), r(), r()).prop_map(|(rd, ra, rb)| Instruction::Xor { rd, ra, rb }),
+        (r(), r()).prop_map(|(rd, ra)| Instruction::Not { rd, ra }),
+        (r(), r(), shift()).prop_map(|(rd, rs, imm)| Instruction::LogicalLeftShift { rd, rs, imm }),
+        (r(), r(), shift()).prop_map(|(rd, rs, imm)| Instruction::LogicalRightShift {
+            rd,
+            rs,
+            imm
+        }),
+        (r(), r(), shift()).prop_map(|(rd, rs, imm)| Instruction::ArithmeticRightShift {
+            rd,
+            rs,
+            imm
+        }),
+        (r(), r(), shift()).prop_map(|(rd, rs, imm)| Instruction::RotateRight { rd, rs, imm }),
+        (r(), byte()).prop_map(|(rd, imm)| Instruction::AddImmediate { rd, imm }),
+        (r(), byte()).prop_map(|(rd, imm)| Instruction::SubImmediate { rd, imm }),
+        (r(), r()).prop_map(|(ra, rb)| Instruction::Compare { ra, rb }),
+        (r(), r()).prop_map(|(ra, rb)| Instruction::CompareLowBytes { ra, rb }),
+        (r(), r()).prop_map(|(ra, rb)| Instruction::CompareHighBytes { ra, rb }),
+        (r(), byte()).prop_map(|(rs, imm)| Instruction::CompareImmediateWithLowByte { rs, imm }),
+        (r(), byte()).prop_map(|(rs, imm)| Instruction::CompareImmediateWithHighByte { rs, imm }),
+        (r(), byte()).prop_map(|(rd, imm)| Instruction::MoveImmediateToLowByte { rd, imm }),
+        (r(), byte()).prop_map(|(rd, imm)| Instruction::MoveImmediateToHighByte { rd, imm }),
+        (r(), r()).prop_map(|(rd, rs)| Instruction::LoadWord { rd, rs }),
+        (r(), r()).prop_map(|(rd, rs)| Instruction::LoadToLowByte { rd, rs }),
+        (r(), r()).prop_map(|(rd, rs)| Instruction::LoadToHighByte { rd, rs }),
+        (r(), r()).prop_map(|(rs, rd)| Instruction::StoreWord { rs, rd }),
+        (r(), r()).prop_map(|(rs, rd)| Instruction::StoreFromLowByte { rs, rd }),
+        (r(), r()).prop_map(|(rs, rd)| Instruction::StoreFromHighByte { rs, rd }),
+        (-2048..2048i16).prop_map(|offset| Instruction::JumpToOffset { offset }),
+        r().prop_map(|rs| Instruction::JumpToPointer { rs }),
+        branch().prop_map(|offset| Instruction::BranchIfCarry { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfNotCarry { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfOverflow { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfNotOverflow { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfZero { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfNotZero { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfSigned { offset }),
+        branch().prop_map(|offset| Instruction::BranchIfNotSigned { offset }),
+        Just(Instruction::Halt),
+    ]
+}
+
+fn assemble(instructions: &[Instruction]) -> Vec<u8> {
+    let mut source = format!("#bank code\n#addr 0x{BASE:04x}\n");
+    for (index, instruction) in instructions.iter().enumerate() {
+        let address = BASE + index as u16 * 2;
+        source.push_str(&instruction.to_assembly(address));
+        source.push('\n');
+    }
+    let case = CASE.fetch_add(1, Ordering::Relaxed);
+    let path = common::temp_path(&format!("roundtrip-{case}.asm"));
+    fs::write(&path, &source).unwrap();
+    let binary = assemble_to_binary(path.to_str().unwrap(), &AssemblerOptions::default());
+    fs::remove_file(&path).unwrap();
+    binary.unwrap_or_else(|error| panic!("{error}\n{source}"))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn assembler_matches_decoder(instructions in prop::collection::vec(instruction(), 1..32)) {
+        let binary = assemble(&instructions);
+        let code = &binary[BASE as usize..];
+        prop_assert_eq!(code.len(), instructions.len() * 2);
+        for (index, instruction) in instructions.iter().enumerate() {
+            let word = u16::from_le_bytes([code[index * 2], code[index * 2 + 1]]);
+            prop_assert_eq!(instruction.encode(), word);
+            let decoded = Cpu::decode(word).unwrap();
+            prop_assert_eq!(
+                decoded,
+                *instruction,
+                "{} assembled to 0x{:04x}",
+                instruction.to_assembly(BASE + index as u16 * 2),
+                word
+            );
+        }
+    }
+}
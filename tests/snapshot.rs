@@ -0,0 +1,38 @@
+mod common;
+
+use risc16::emulator::Cpu;
+use risc16::snapshot::Snapshot;
+
+fn load() -> Cpu {
+    common::load(&common::assemble("examples/fibonacci.asm"))
+}
+
+#[test]
+fn resume_from_snapshot() {
+    let mut expected = load();
+    expected.run().unwrap();
+
+    let mut cpu = load();
+    for _ in 0..40 {
+        cpu.step().unwrap();
+    }
+    let bytes = Snapshot::capture(&cpu).to_bytes();
+    let snapshot = Snapshot::from_bytes(&bytes).unwrap();
+    assert_eq!(snapshot.cycles, 40);
+
+    let mut resumed = load();
+    snapshot.restore(&mut resumed);
+    assert!(!resumed.halted);
+    resumed.run().unwrap();
+    assert_eq!(resumed.registers, expected.registers);
+    assert_eq!(resumed.program_counter, expected.program_counter);
+    assert_eq!(resumed.memory, expected.memory);
+    assert_eq!(resumed.flags, expected.flags);
+}
+
+#[test]
+fn reject_truncated_snapshot() {
+    let bytes = Snapshot::capture(&load()).to_bytes();
+    assert!(Snapshot::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Snapshot::from_bytes(b"RX16").is_err());
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use risc16::assembler::AssemblerOptions;
+use risc16::testing::{TestOptions, run_tests};
+use risc16::timing::Timing;
+use std::fs;
+
+fn run(name: &str, source: &str) -> anyhow::Result<()> {
+    let directory = common::temp_path(name);
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("case.test.asm"), source).unwrap();
+    let result = run_tests(
+        vec![directory.to_str().unwrap().to_string()],
+        TestOptions {
+            cycles: 1000,
+            timing: Timing::default(),
+            coverage: None,
+            assembler: AssemblerOptions::default(),
+        },
+    );
+    fs::remove_dir_all(&directory).unwrap();
+    result
+}
+
+const PROGRAM: &str = "#bank code\n LI r1, -2\n HLT\n#bank data\nvalue: #d8 44, 0xff\n";
+
+#[test]
+fn expectations() {
+    let pass = "; expect r1 == -2\n; expect r1 == 0xfffe\n; expect mem[value..value+2] == 44, -1\n";
+    run("testing-pass", &format!("{pass}{PROGRAM}")).unwrap();
+    assert!(
+        run(
+            "testing-fail",
+            &format!("; expect mem[value] == 45\n{PROGRAM}")
+        )
+        .is_err()
+    );
+    assert!(
+        run(
+            "testing-count",
+            &format!("; expect mem[value..value+2] == 44\n{PROGRAM}")
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn rejects_values_out_of_range() {
+    assert!(
+        run(
+            "testing-byte",
+            &format!("; expect mem[value] == 300\n{PROGRAM}")
+        )
+        .is_err()
+    );
+    assert!(
+        run(
+            "testing-negative",
+            &format!("; expect mem[value+1] == -257\n{PROGRAM}")
+        )
+        .is_err()
+    );
+    assert!(
+        run(
+            "testing-word",
+            &format!("; expect r1 == 0x1fffe\n{PROGRAM}")
+        )
+        .is_err()
+    );
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use risc16::emulator::Cpu;
+use risc16::profile::Profile;
+use risc16::timing::{CLASSES, Latency, Timing};
+
+fn run(timing: Timing) -> Cpu {
+    let mut cpu = common::load(&common::assemble("examples/memcopy.asm"));
+    cpu.timing = timing;
+    cpu.profile = Some(Profile::default());
+    cpu.run().unwrap();
+    cpu
+}
+
+#[test]
+fn default_timing_counts_instructions() {
+    let cpu = run(Timing::default());
+    assert_eq!(cpu.cycles, 102);
+}
+
+#[test]
+fn latencies_and_penalties() {
+    let timing: Timing = "# hardware timing\nload = 2\nstore=3 ; byte stores\ntaken=2\nmemory=1\n"
+        .parse()
+        .unwrap();
+    let cpu = run(timing);
+    let profile = cpu.profile.as_ref().unwrap();
+    let count = |class| profile.classes.get(class).copied().unwrap_or(0);
+    let taken: u64 = profile.branches.values().map(|(taken, _)| taken).sum();
+    let expected = profile.classes.values().sum::<u64>()
+        + count("load") * 2
+        + count("store") * 3
+        + (taken + count("jump")) * 2;
+    assert_eq!(taken, 15);
+    assert_eq!(cpu.cycles, expected);
+    assert_eq!(profile.cycles["load"], count("load") * 3);
+    assert_eq!(profile.cycles.values().sum::<u64>(), cpu.cycles);
+}
+
+#[test]
+fn invalid_latencies() {
+    assert!("fetch=2".parse::<Timing>().is_err());
+    assert!("alu".parse::<Latency>().is_err());
+    let timing = Timing::load(None, &["halt=0".parse().unwrap()]).unwrap();
+    let halt = CLASSES.iter().position(|class| *class == "halt").unwrap();
+    assert_eq!(timing.classes[halt], 0);
+}